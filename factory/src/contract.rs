@@ -0,0 +1,162 @@
+use crate::error::FactoryError;
+use crate::events;
+use crate::product;
+use crate::storage;
+use crate::tokenizer;
+use product::{get_product, get_product_count, get_product_index, push_product, Product};
+use soroban_sdk::{contract, contractimpl, contractmeta, token, Address, BytesN, Env, String};
+use storage::{
+    bump_instance, get_admin, get_token_wasm_hash, get_tokenizer_wasm_hash, is_initialized,
+    require_admin, require_initialized, set_admin, set_token_wasm_hash, set_tokenizer_wasm_hash,
+};
+
+// Metadata that is added on to the WASM custom section
+contractmeta!(key = "Description", val = "Money market product factory");
+
+pub trait ExcellarFactoryTrait {
+    fn initialize(
+        e: Env,
+        admin: Address,
+        tokenizer_wasm_hash: BytesN<32>,
+        token_wasm_hash: BytesN<32>,
+    ) -> Result<(), FactoryError>;
+
+    fn admin(e: Env) -> Result<Address, FactoryError>;
+
+    fn tokenizer_wasm_hash(e: Env) -> Result<BytesN<32>, FactoryError>;
+
+    fn token_wasm_hash(e: Env) -> Result<BytesN<32>, FactoryError>;
+
+    fn set_wasm_hashes(
+        e: Env,
+        tokenizer_wasm_hash: BytesN<32>,
+        token_wasm_hash: BytesN<32>,
+    ) -> Result<(), FactoryError>;
+
+    fn create_product(
+        e: Env,
+        collateral: Address,
+        admin: Address,
+        price_decimals: u32,
+        name: String,
+        symbol: String,
+        decimals: u32,
+    ) -> Result<Product, FactoryError>;
+
+    fn product_count(e: Env) -> Result<u32, FactoryError>;
+
+    fn product(e: Env, index: u32) -> Result<Product, FactoryError>;
+
+    fn product_by_symbol(e: Env, symbol: String) -> Result<Product, FactoryError>;
+}
+
+#[contract]
+pub struct ExcellarFactory;
+
+#[contractimpl]
+impl ExcellarFactoryTrait for ExcellarFactory {
+    fn initialize(
+        e: Env,
+        admin: Address,
+        tokenizer_wasm_hash: BytesN<32>,
+        token_wasm_hash: BytesN<32>,
+    ) -> Result<(), FactoryError> {
+        if is_initialized(&e) {
+            return Err(FactoryError::AlreadyInitialized);
+        }
+
+        set_admin(&e, &admin);
+        set_tokenizer_wasm_hash(&e, &tokenizer_wasm_hash);
+        set_token_wasm_hash(&e, &token_wasm_hash);
+        bump_instance(&e);
+        Ok(())
+    }
+
+    fn admin(e: Env) -> Result<Address, FactoryError> {
+        require_initialized(&e)?;
+        Ok(get_admin(&e))
+    }
+
+    fn tokenizer_wasm_hash(e: Env) -> Result<BytesN<32>, FactoryError> {
+        require_initialized(&e)?;
+        Ok(get_tokenizer_wasm_hash(&e))
+    }
+
+    fn token_wasm_hash(e: Env) -> Result<BytesN<32>, FactoryError> {
+        require_initialized(&e)?;
+        Ok(get_token_wasm_hash(&e))
+    }
+
+    fn set_wasm_hashes(
+        e: Env,
+        tokenizer_wasm_hash: BytesN<32>,
+        token_wasm_hash: BytesN<32>,
+    ) -> Result<(), FactoryError> {
+        require_admin(&e)?;
+        set_tokenizer_wasm_hash(&e, &tokenizer_wasm_hash);
+        set_token_wasm_hash(&e, &token_wasm_hash);
+        Ok(())
+    }
+
+    fn create_product(
+        e: Env,
+        collateral: Address,
+        admin: Address,
+        price_decimals: u32,
+        name: String,
+        symbol: String,
+        decimals: u32,
+    ) -> Result<Product, FactoryError> {
+        require_admin(&e)?;
+        if get_product_index(&e, symbol.clone()).is_some() {
+            return Err(FactoryError::SymbolTaken);
+        }
+        // Shares are minted in units of the collateral.
+        if decimals != token::Client::new(&e, &collateral).decimals() {
+            return Err(FactoryError::DecimalsMismatch);
+        }
+
+        let tokenizer_contract =
+            tokenizer::create_contract(&e, get_tokenizer_wasm_hash(&e), &symbol);
+        let tokenizer = tokenizer::Client::new(&e, &tokenizer_contract);
+        tokenizer.initialize(
+            &get_token_wasm_hash(&e),
+            &collateral,
+            &admin,
+            &price_decimals,
+            &tokenizer::ShareToken {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                decimals,
+            },
+        );
+
+        let product = Product {
+            name,
+            symbol,
+            token: tokenizer.xusg_id(),
+            tokenizer: tokenizer_contract,
+            collateral,
+        };
+        let index = push_product(&e, &product);
+
+        events::product_created(&e, index, product.clone());
+        Ok(product)
+    }
+
+    fn product_count(e: Env) -> Result<u32, FactoryError> {
+        require_initialized(&e)?;
+        Ok(get_product_count(&e))
+    }
+
+    fn product(e: Env, index: u32) -> Result<Product, FactoryError> {
+        require_initialized(&e)?;
+        get_product(&e, index).ok_or(FactoryError::ProductNotFound)
+    }
+
+    fn product_by_symbol(e: Env, symbol: String) -> Result<Product, FactoryError> {
+        require_initialized(&e)?;
+        let index = get_product_index(&e, symbol).ok_or(FactoryError::ProductNotFound)?;
+        get_product(&e, index).ok_or(FactoryError::ProductNotFound)
+    }
+}
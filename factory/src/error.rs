@@ -0,0 +1,12 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum FactoryError {
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+    SymbolTaken = 3,
+    ProductNotFound = 4,
+    DecimalsMismatch = 5,
+}
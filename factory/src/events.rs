@@ -0,0 +1,7 @@
+use crate::product::Product;
+use soroban_sdk::{Env, Symbol};
+
+pub fn product_created(e: &Env, index: u32, product: Product) {
+    e.events()
+        .publish((Symbol::new(e, "product_created"), index), product);
+}
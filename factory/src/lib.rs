@@ -0,0 +1,11 @@
+#![no_std]
+
+mod contract;
+mod error;
+mod events;
+mod product;
+mod storage;
+mod test;
+mod tokenizer;
+
+pub use crate::contract::ExcellarFactoryClient;
@@ -0,0 +1,39 @@
+use crate::storage::{get_record, set_record, DataKey, RecordKey};
+use soroban_sdk::{contracttype, Address, Env, String};
+
+/// A tokenizer deployed by the factory together with its share token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Product {
+    pub name: String,
+    pub symbol: String,
+    pub tokenizer: Address,
+    pub token: Address,
+    pub collateral: Address,
+}
+
+pub fn get_product_count(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::ProductCount)
+        .unwrap_or(0)
+}
+
+pub fn get_product(e: &Env, index: u32) -> Option<Product> {
+    get_record(e, &RecordKey::Product(index))
+}
+
+pub fn get_product_index(e: &Env, symbol: String) -> Option<u32> {
+    get_record(e, &RecordKey::ProductIndex(symbol))
+}
+
+/// Appends `product` to the registry and returns its index.
+pub fn push_product(e: &Env, product: &Product) -> u32 {
+    let index = get_product_count(e);
+    set_record(e, &RecordKey::Product(index), product);
+    set_record(e, &RecordKey::ProductIndex(product.symbol.clone()), &index);
+    e.storage()
+        .instance()
+        .set(&DataKey::ProductCount, &(index + 1));
+    index
+}
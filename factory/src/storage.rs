@@ -0,0 +1,109 @@
+use crate::error::FactoryError;
+use soroban_sdk::{
+    contracttype, Address, BytesN, ConversionError, Env, IntoVal, String, TryFromVal, Val,
+};
+
+pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const RECORD_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const RECORD_LIFETIME_THRESHOLD: u32 = RECORD_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+#[derive(Clone, Copy)]
+#[repr(u32)]
+pub enum DataKey {
+    Admin = 1,
+    TokenizerWasmHash = 2,
+    TokenWasmHash = 3,
+    ProductCount = 4,
+}
+impl TryFromVal<Env, DataKey> for Val {
+    type Error = ConversionError;
+
+    fn try_from_val(_env: &Env, v: &DataKey) -> Result<Self, Self::Error> {
+        Ok((*v as u32).into())
+    }
+}
+
+/// Keys of the product registry, kept in persistent storage.
+#[derive(Clone)]
+#[contracttype]
+pub enum RecordKey {
+    Product(u32),
+    ProductIndex(String),
+}
+
+pub fn bump_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .bump(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn bump_record(e: &Env, key: &RecordKey) {
+    e.storage()
+        .persistent()
+        .bump(key, RECORD_LIFETIME_THRESHOLD, RECORD_BUMP_AMOUNT);
+}
+
+/// Reads a persistent record, extending its TTL if it exists.
+pub fn get_record<V: TryFromVal<Env, Val>>(e: &Env, key: &RecordKey) -> Option<V> {
+    let value = e.storage().persistent().get(key);
+    if value.is_some() {
+        bump_record(e, key);
+    }
+    value
+}
+
+pub fn set_record<V: IntoVal<Env, Val>>(e: &Env, key: &RecordKey, value: &V) {
+    e.storage().persistent().set(key, value);
+    bump_record(e, key);
+}
+
+pub fn get_admin(e: &Env) -> Address {
+    e.storage().instance().get(&DataKey::Admin).unwrap()
+}
+
+pub fn set_admin(e: &Env, admin: &Address) {
+    e.storage().instance().set(&DataKey::Admin, admin)
+}
+
+pub fn get_tokenizer_wasm_hash(e: &Env) -> BytesN<32> {
+    e.storage()
+        .instance()
+        .get(&DataKey::TokenizerWasmHash)
+        .unwrap()
+}
+
+pub fn set_tokenizer_wasm_hash(e: &Env, hash: &BytesN<32>) {
+    e.storage()
+        .instance()
+        .set(&DataKey::TokenizerWasmHash, hash)
+}
+
+pub fn get_token_wasm_hash(e: &Env) -> BytesN<32> {
+    e.storage().instance().get(&DataKey::TokenWasmHash).unwrap()
+}
+
+pub fn set_token_wasm_hash(e: &Env, hash: &BytesN<32>) {
+    e.storage().instance().set(&DataKey::TokenWasmHash, hash)
+}
+
+pub fn is_initialized(e: &Env) -> bool {
+    e.storage().instance().has(&DataKey::Admin)
+}
+
+/// Fails before `initialize` and otherwise keeps the instance alive.
+pub fn require_initialized(e: &Env) -> Result<(), FactoryError> {
+    if !is_initialized(e) {
+        return Err(FactoryError::NotInitialized);
+    }
+    bump_instance(e);
+    Ok(())
+}
+
+pub fn require_admin(e: &Env) -> Result<(), FactoryError> {
+    require_initialized(e)?;
+    get_admin(e).require_auth();
+    Ok(())
+}
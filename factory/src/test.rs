@@ -0,0 +1,221 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::error::FactoryError;
+use crate::{tokenizer, ExcellarFactoryClient};
+
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    token, vec, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
+};
+
+const PRICE_DECIMALS: u32 = 7;
+
+fn create_token_contract<'a>(e: &Env, admin: &Address) -> token::Client<'a> {
+    token::Client::new(e, &e.register_stellar_asset_contract(admin.clone()))
+}
+
+fn assert_last_event(e: &Env, event: (Address, Vec<Val>, Val)) {
+    let events = e.events().all();
+    assert_eq!(events.slice(events.len() - 1..), vec![e, event]);
+}
+
+fn install_token_wasm(e: &Env) -> BytesN<32> {
+    soroban_sdk::contractimport!(
+        file = "../token/target/wasm32-unknown-unknown/release/excellar_token_contract.wasm"
+    );
+    e.install_contract_wasm(WASM)
+}
+
+mod token_contract {
+    soroban_sdk::contractimport!(
+        file = "../token/target/wasm32-unknown-unknown/release/excellar_token_contract.wasm"
+    );
+}
+
+/// A collateral token with other decimals than the 7 of Stellar assets.
+fn create_token_with_decimals<'a>(
+    e: &Env,
+    admin: &Address,
+    decimals: u32,
+) -> token_contract::Client<'a> {
+    let token =
+        token_contract::Client::new(e, &e.register_contract_wasm(None, token_contract::WASM));
+    token.initialize(
+        admin,
+        &decimals,
+        &String::from_str(e, "USD Coin"),
+        &String::from_str(e, "USDC"),
+    );
+    token
+}
+
+fn install_tokenizer_wasm(e: &Env) -> BytesN<32> {
+    e.install_contract_wasm(tokenizer::WASM)
+}
+
+fn create_factory_contract<'a>(e: &Env, admin: &Address) -> ExcellarFactoryClient<'a> {
+    let factory = ExcellarFactoryClient::new(
+        e,
+        &e.register_contract(None, crate::contract::ExcellarFactory {}),
+    );
+    factory.initialize(admin, &install_tokenizer_wasm(e), &install_token_wasm(e));
+    factory
+}
+
+#[test]
+fn test_create_product() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let fund_admin = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let factory = create_factory_contract(&e, &admin1);
+    let name = String::from_str(&e, "Excellar Mint");
+    let symbol = String::from_str(&e, "XUSG");
+
+    let product = factory.create_product(
+        &token_usdc.address,
+        &fund_admin,
+        &PRICE_DECIMALS,
+        &name,
+        &symbol,
+        &7,
+    );
+    assert_eq!(product.name, name);
+    assert_eq!(product.symbol, symbol);
+    assert_eq!(product.collateral, token_usdc.address);
+    assert_last_event(
+        &e,
+        (
+            factory.address.clone(),
+            (Symbol::new(&e, "product_created"), 0_u32).into_val(&e),
+            product.clone().into_val(&e),
+        ),
+    );
+
+    let tokenizer = tokenizer::Client::new(&e, &product.tokenizer);
+    assert_eq!(tokenizer.admin(), fund_admin);
+    assert_eq!(tokenizer.xusg_id(), product.token);
+    assert_eq!(tokenizer.price_decimals(), PRICE_DECIMALS);
+    let share = token::Client::new(&e, &product.token);
+    assert_eq!(share.symbol(), symbol);
+    assert_eq!(share.decimals(), 7);
+
+    assert_eq!(factory.product_count(), 1);
+    assert_eq!(factory.product(&0), product);
+    assert_eq!(factory.product_by_symbol(&symbol), product);
+}
+
+#[test]
+fn test_products_share_collateral() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let factory = create_factory_contract(&e, &admin1);
+
+    let create = |name: &str, symbol: &str, decimals: u32| {
+        factory.try_create_product(
+            &token_usdc.address,
+            &admin1,
+            &PRICE_DECIMALS,
+            &String::from_str(&e, name),
+            &String::from_str(&e, symbol),
+            &decimals,
+        )
+    };
+    let short = create("Excellar Short Treasuries", "XUSGS", 7)
+        .unwrap()
+        .unwrap();
+    let long = create("Excellar Long Treasuries", "XUSGL", 7)
+        .unwrap()
+        .unwrap();
+    assert_ne!(short.tokenizer, long.tokenizer);
+    assert_ne!(short.token, long.token);
+    assert_eq!(token::Client::new(&e, &long.token).decimals(), 7);
+
+    assert_eq!(
+        create("Excellar Short Treasuries", "XUSGS", 7),
+        Err(Ok(FactoryError::SymbolTaken))
+    );
+    assert_eq!(
+        create("Excellar Bills", "XUSGB", 6),
+        Err(Ok(FactoryError::DecimalsMismatch))
+    );
+    assert_eq!(factory.product_count(), 2);
+    assert_eq!(
+        vec![&e, factory.product(&0), factory.product(&1)],
+        vec![&e, short, long]
+    );
+    assert_eq!(
+        factory.try_product(&2),
+        Err(Ok(FactoryError::ProductNotFound))
+    );
+    assert_eq!(
+        factory.try_product_by_symbol(&String::from_str(&e, "XEUR")),
+        Err(Ok(FactoryError::ProductNotFound))
+    );
+}
+
+#[test]
+fn test_deposit_into_six_decimal_product() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let user1 = Address::random(&e);
+    let collateral = create_token_with_decimals(&e, &admin1, 6);
+    let factory = create_factory_contract(&e, &admin1);
+
+    let product = factory.create_product(
+        &collateral.address,
+        &admin1,
+        &PRICE_DECIMALS,
+        &String::from_str(&e, "Excellar Bills"),
+        &String::from_str(&e, "XUSGB"),
+        &6,
+    );
+    let tokenizer = tokenizer::Client::new(&e, &product.tokenizer);
+    tokenizer.set_allowlist_enabled(&false);
+
+    // 2.5 USDC buys 2.5 shares at a price of 1.
+    collateral.mint(&user1, &2_500_000);
+    let deposit = tokenizer.deposit(&user1, &collateral.address, &2_500_000);
+    assert_eq!(deposit.xusg, 2_500_000);
+    let share = token::Client::new(&e, &product.token);
+    assert_eq!(share.decimals(), 6);
+    assert_eq!(share.balance(&user1), 2_500_000);
+    assert_eq!(tokenizer.cash_reserves(), 2_500_000);
+
+    // At a price of 1.2, one share is worth 1.2 USDC.
+    tokenizer.set_etf_market_value(&admin1, &500_000);
+    assert_eq!(tokenizer.price(), 12 * 10_i128.pow(PRICE_DECIMALS) / 10);
+    tokenizer.withdraw(&user1, &collateral.address, &1_000_000);
+    assert_eq!(collateral.balance(&user1), 1_200_000);
+}
+
+#[test]
+fn test_initialization_errors() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let factory = ExcellarFactoryClient::new(
+        &e,
+        &e.register_contract(None, crate::contract::ExcellarFactory {}),
+    );
+    assert_eq!(
+        factory.try_product_count(),
+        Err(Ok(FactoryError::NotInitialized))
+    );
+
+    let tokenizer_wasm_hash = install_tokenizer_wasm(&e);
+    let token_wasm_hash = install_token_wasm(&e);
+    factory.initialize(&admin1, &tokenizer_wasm_hash, &token_wasm_hash);
+    assert_eq!(
+        factory.try_initialize(&admin1, &tokenizer_wasm_hash, &token_wasm_hash),
+        Err(Ok(FactoryError::AlreadyInitialized))
+    );
+    assert_eq!(factory.admin(), admin1);
+    assert_eq!(factory.tokenizer_wasm_hash(), tokenizer_wasm_hash);
+    assert_eq!(factory.token_wasm_hash(), token_wasm_hash);
+}
@@ -0,0 +1,15 @@
+#![allow(unused)]
+use soroban_sdk::{xdr::ToXdr, Address, BytesN, Env, String};
+
+soroban_sdk::contractimport!(
+    file = "../tokenizer/target/wasm32-unknown-unknown/release/excellar_tokenizer_contract.wasm"
+);
+
+/// Deploys a tokenizer at an address derived from the factory and the
+/// product symbol.
+pub fn create_contract(e: &Env, tokenizer_wasm_hash: BytesN<32>, symbol: &String) -> Address {
+    let salt = e.crypto().sha256(&symbol.clone().to_xdr(e));
+    e.deployer()
+        .with_current_contract(salt)
+        .deploy(tokenizer_wasm_hash)
+}
@@ -0,0 +1,37 @@
+use crate::error::GovernanceError;
+use crate::storage::DataKey;
+use soroban_sdk::{contracttype, Address, Env};
+
+/// An admin handover that `admin` still has to accept.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub admin: Address,
+    /// Ledger timestamp from which the proposal can no longer be accepted.
+    pub expires_at: Option<u64>,
+}
+
+pub fn get_pending_admin(e: &Env) -> Option<PendingAdmin> {
+    e.storage().instance().get(&DataKey::PendingAdmin)
+}
+
+pub fn set_pending_admin(e: &Env, pending: &PendingAdmin) {
+    e.storage().instance().set(&DataKey::PendingAdmin, pending)
+}
+
+pub fn remove_pending_admin(e: &Env) {
+    e.storage().instance().remove(&DataKey::PendingAdmin)
+}
+
+/// Returns the proposed admin once it has authorized taking over.
+pub fn require_pending_admin(e: &Env) -> Result<Address, GovernanceError> {
+    let pending = get_pending_admin(e).ok_or(GovernanceError::NoPendingAdmin)?;
+    if let Some(expires_at) = pending.expires_at {
+        if e.ledger().timestamp() >= expires_at {
+            return Err(GovernanceError::AdminProposalExpired);
+        }
+    }
+
+    pending.admin.require_auth();
+    Ok(pending.admin)
+}
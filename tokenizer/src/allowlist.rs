@@ -0,0 +1,71 @@
+use crate::error::ExcellarError;
+use crate::storage::{get_record, remove_record, set_record, DataKey, RecordKey};
+use soroban_sdk::{contracttype, Address, Env};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum InvestorCategory {
+    Retail = 0,
+    Professional = 1,
+    Institutional = 2,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AllowlistEntry {
+    pub category: InvestorCategory,
+    /// Ledger timestamp from which the onboarding is no longer valid.
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Investor {
+    pub account: Address,
+    pub category: InvestorCategory,
+    pub expires_at: u64,
+}
+
+pub fn is_allowlist_enabled(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get(&DataKey::AllowlistEnabled)
+        .unwrap_or(true)
+}
+
+pub fn set_allowlist_enabled(e: &Env, enabled: bool) {
+    e.storage()
+        .instance()
+        .set(&DataKey::AllowlistEnabled, &enabled)
+}
+
+pub fn get_investor(e: &Env, account: Address) -> Option<AllowlistEntry> {
+    get_record(e, &RecordKey::Investor(account))
+}
+
+pub fn set_investor(e: &Env, investor: Investor) {
+    set_record(
+        e,
+        &RecordKey::Investor(investor.account),
+        &AllowlistEntry {
+            category: investor.category,
+            expires_at: investor.expires_at,
+        },
+    )
+}
+
+pub fn remove_investor(e: &Env, account: Address) {
+    remove_record(e, &RecordKey::Investor(account))
+}
+
+pub fn require_allowlisted(e: &Env, account: &Address) -> Result<(), ExcellarError> {
+    if !is_allowlist_enabled(e) {
+        return Ok(());
+    }
+
+    let entry = get_investor(e, account.clone()).ok_or(ExcellarError::NotAllowlisted)?;
+    if e.ledger().timestamp() >= entry.expires_at {
+        return Err(ExcellarError::AllowlistExpired);
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use crate::fees::BPS_DENOMINATOR;
+use crate::storage::{get_record, set_record, DataKey, RecordKey};
+use soroban_sdk::{contracttype, Address, BytesN, Env, String, Vec};
+
+/// Reserve ratio below which `reserve_ratio` flags a shortfall unless the
+/// admin configures another floor: full backing.
+pub const DEFAULT_RESERVE_RATIO_FLOOR_BPS: u32 = BPS_DENOMINATOR as u32;
+
+/// A proof-of-reserves report posted by an auditor or custodian.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Attestation {
+    pub attester: Address,
+    /// Hash of the off-chain report document.
+    pub report_hash: BytesN<32>,
+    /// Assets held in custody for the fund, in USDC.
+    pub custody_total: i128,
+    /// Timestamp the report is as of.
+    pub reported_at: u64,
+    pub uri: String,
+    /// Ledger timestamp at which the report was posted.
+    pub recorded_at: u64,
+}
+
+/// The latest attested custody total against the value of the XUSG in
+/// circulation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ReserveRatio {
+    pub attested: i128,
+    pub liabilities: i128,
+    /// `attested / liabilities` in basis points, `u32::MAX` when there are no
+    /// liabilities.
+    pub ratio_bps: u32,
+    pub floor_bps: u32,
+    pub below_floor: bool,
+}
+
+pub fn get_attestation_count(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::AttestationCount)
+        .unwrap_or(0)
+}
+
+pub fn push_attestation(e: &Env, attestation: &Attestation) {
+    let index = get_attestation_count(e);
+    set_record(e, &RecordKey::Attestation(index), attestation);
+    e.storage()
+        .instance()
+        .set(&DataKey::AttestationCount, &(index + 1));
+}
+
+pub fn get_attestation(e: &Env, index: u32) -> Option<Attestation> {
+    get_record(e, &RecordKey::Attestation(index))
+}
+
+pub fn get_latest_attestation(e: &Env) -> Option<Attestation> {
+    get_attestation_count(e)
+        .checked_sub(1)
+        .and_then(|index| get_attestation(e, index))
+}
+
+/// Up to `limit` attestations, oldest first, starting at `start`.
+pub fn get_attestations(e: &Env, start: u32, limit: u32) -> Vec<Attestation> {
+    let end = start.saturating_add(limit).min(get_attestation_count(e));
+    let mut attestations = Vec::new(e);
+    for index in start..end {
+        attestations.push_back(get_attestation(e, index).unwrap());
+    }
+    attestations
+}
+
+pub fn get_reserve_ratio_floor(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::ReserveRatioFloor)
+        .unwrap_or(DEFAULT_RESERVE_RATIO_FLOOR_BPS)
+}
+
+pub fn set_reserve_ratio_floor(e: &Env, bps: u32) {
+    e.storage()
+        .instance()
+        .set(&DataKey::ReserveRatioFloor, &bps)
+}
+
+pub fn reserve_ratio(e: &Env, attested: i128, liabilities: i128) -> ReserveRatio {
+    let ratio_bps = if liabilities <= 0 {
+        u32::MAX
+    } else {
+        let ratio = attested
+            .checked_mul(BPS_DENOMINATOR)
+            .map_or(i128::MAX, |scaled| scaled / liabilities);
+        ratio.clamp(0, u32::MAX as i128) as u32
+    };
+    let floor_bps = get_reserve_ratio_floor(e);
+    ReserveRatio {
+        attested,
+        liabilities,
+        ratio_bps,
+        floor_bps,
+        below_floor: ratio_bps < floor_bps,
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::ExcellarError;
+use crate::oracle::{OracleConfig, PriceOracleClient};
+use crate::storage::{get_token_usdc, DataKey, RecordKey};
+use crate::utils::{checked_add, checked_sub, mul_div, pow10};
+use soroban_sdk::{contracttype, token, Address, Env, Vec};
+
+/// Largest supported collateral precision, in line with the XUSG price.
+pub(crate) const MAX_COLLATERAL_DECIMALS: u32 = 18;
+
+/// What one whole token of a collateral asset is worth in base (USDC) units.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ConversionRate {
+    Fixed(i128),
+    /// The oracle's last price for the asset, rescaled to base units.
+    Oracle(OracleConfig),
+}
+
+/// An asset accepted for subscriptions and redemptions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Collateral {
+    pub decimals: u32,
+    pub rate: ConversionRate,
+}
+
+/// Every accepted asset, the base asset first.
+pub fn get_collateral_assets(e: &Env) -> Vec<Address> {
+    e.storage()
+        .instance()
+        .get(&DataKey::CollateralAssets)
+        .unwrap_or(Vec::new(e))
+}
+
+pub fn get_collateral(e: &Env, asset: &Address) -> Option<Collateral> {
+    e.storage()
+        .instance()
+        .get(&RecordKey::Collateral(asset.clone()))
+}
+
+pub fn set_collateral(e: &Env, asset: Address, collateral: &Collateral) {
+    let mut assets = get_collateral_assets(e);
+    if !assets.contains(&asset) {
+        assets.push_back(asset.clone());
+        e.storage()
+            .instance()
+            .set(&DataKey::CollateralAssets, &assets);
+    }
+    e.storage()
+        .instance()
+        .set(&RecordKey::Collateral(asset), collateral);
+}
+
+pub fn remove_collateral(e: &Env, asset: &Address) {
+    let mut assets = get_collateral_assets(e);
+    if let Some(index) = assets.first_index_of(asset) {
+        assets.remove(index);
+        e.storage()
+            .instance()
+            .set(&DataKey::CollateralAssets, &assets);
+    }
+    e.storage()
+        .instance()
+        .remove(&RecordKey::Collateral(asset.clone()));
+    e.storage()
+        .instance()
+        .remove(&RecordKey::CarriedRate(asset.clone()));
+}
+
+/// Longest a collateral oracle price may go without an update before
+/// conversions fail with `StalePrice`; 0 disables the check.
+pub fn get_collateral_heartbeat(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::CollateralHeartbeat)
+        .unwrap_or(0)
+}
+
+pub fn set_collateral_heartbeat(e: &Env, seconds: u64) {
+    e.storage()
+        .instance()
+        .set(&DataKey::CollateralHeartbeat, &seconds)
+}
+
+/// The collateral entry the base asset is registered with: one token is
+/// worth exactly one token.
+pub fn base_collateral(e: &Env) -> Collateral {
+    let decimals = token::Client::new(e, &get_token_usdc(e)).decimals();
+    Collateral {
+        decimals,
+        rate: ConversionRate::Fixed(10_i128.pow(decimals)),
+    }
+}
+
+/// Part of the cash reserves held in `asset`, in the asset's own units.
+pub fn get_asset_reserves(e: &Env, asset: &Address) -> i128 {
+    e.storage()
+        .instance()
+        .get(&RecordKey::AssetReserves(asset.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_asset_reserves(e: &Env, asset: &Address, amount: i128) {
+    e.storage()
+        .instance()
+        .set(&RecordKey::AssetReserves(asset.clone()), &amount)
+}
+
+pub fn add_to_asset_reserves(e: &Env, asset: &Address, amount: i128) -> Result<(), ExcellarError> {
+    set_asset_reserves(e, asset, checked_add(get_asset_reserves(e, asset), amount)?);
+    Ok(())
+}
+
+/// Floors at zero: the contract may pay out tokens that were sent to it
+/// directly and never counted as reserves.
+pub fn subtract_from_asset_reserves(e: &Env, asset: &Address, amount: i128) {
+    let reserves = get_asset_reserves(e, asset);
+    set_asset_reserves(e, asset, reserves.saturating_sub(amount).max(0))
+}
+
+/// Converts between a collateral asset and base units at one rate for the
+/// length of an operation.
+pub struct Converter {
+    rate: i128,
+    unit: i128,
+}
+
+impl Converter {
+    pub fn new(e: &Env, asset: &Address) -> Result<Self, ExcellarError> {
+        let collateral = get_collateral(e, asset).ok_or(ExcellarError::UnsupportedCollateral)?;
+        let rate = match collateral.rate {
+            ConversionRate::Fixed(rate) => rate,
+            ConversionRate::Oracle(config) => {
+                let oracle = PriceOracleClient::new(e, &config.oracle);
+                let price_data = oracle
+                    .lastprice(&config.asset)
+                    .ok_or(ExcellarError::OraclePriceUnavailable)?;
+                let heartbeat = get_collateral_heartbeat(e);
+                if heartbeat > 0
+                    && e.ledger().timestamp() > price_data.timestamp.saturating_add(heartbeat)
+                {
+                    return Err(ExcellarError::StalePrice);
+                }
+                let base_decimals = token::Client::new(e, &get_token_usdc(e)).decimals();
+                mul_div(
+                    price_data.price,
+                    pow10(base_decimals)?,
+                    pow10(oracle.decimals())?,
+                )?
+            }
+        };
+        if rate <= 0 {
+            return Err(ExcellarError::OraclePriceUnavailable);
+        }
+
+        Ok(Converter {
+            rate,
+            unit: pow10(collateral.decimals)?,
+        })
+    }
+
+    pub fn to_base(&self, amount: i128) -> Result<i128, ExcellarError> {
+        mul_div(amount, self.rate, self.unit)
+    }
+
+    pub fn to_asset(&self, value: i128) -> Result<i128, ExcellarError> {
+        mul_div(value, self.unit, self.rate)
+    }
+}
+
+/// Rate the reserves held in `asset` were last counted in the cash reserves
+/// at.
+fn get_carried_rate(e: &Env, asset: &Address) -> Option<i128> {
+    e.storage()
+        .instance()
+        .get(&RecordKey::CarriedRate(asset.clone()))
+}
+
+/// Counts the reserves held in `asset` at its current rate from now on and
+/// returns how much their base value moved since they were last counted.
+pub fn revalue_collateral(e: &Env, asset: &Address) -> Result<i128, ExcellarError> {
+    let converter = Converter::new(e, asset)?;
+    let reserves = get_asset_reserves(e, asset);
+    let change = match get_carried_rate(e, asset) {
+        Some(carried) if carried != converter.rate && reserves > 0 => checked_sub(
+            converter.to_base(reserves)?,
+            mul_div(reserves, carried, converter.unit)?,
+        )?,
+        _ => 0,
+    };
+    e.storage()
+        .instance()
+        .set(&RecordKey::CarriedRate(asset.clone()), &converter.rate);
+    Ok(change)
+}
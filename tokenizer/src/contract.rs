@@ -1,33 +1,119 @@
-use crate::error::ExcellarError;
+use crate::admin;
+use crate::allowlist;
+use crate::attestation;
+use crate::collateral;
+use crate::custodian;
+use crate::error::{AccessError, ExcellarError, GovernanceError};
+use crate::events;
+use crate::fees;
+use crate::limits;
+use crate::nav;
+use crate::oracle;
+use crate::pause;
+use crate::receipt;
+use crate::redemption;
+use crate::roles;
 use crate::storage;
+use crate::timelock;
 use crate::token;
+use crate::upgrade;
 use crate::utils;
-use soroban_sdk::{contract, contractimpl, contractmeta, Address, BytesN, Env, IntoVal};
+use soroban_sdk::{
+    contract, contractimpl, contractmeta, Address, Bytes, BytesN, Env, Error, String,
+};
 use storage::{
-    get_cash_reserves, get_etf_market_value, get_fees, get_token_usdc, get_token_xusg,
-    get_total_xusg, require_admin, set_admin, set_cash_reserves, set_etf_market_value, set_fees,
+    get_admin, get_cash_reserves, get_etf_market_value, get_price_decimals, get_token_usdc,
+    get_token_xusg, get_total_xusg, is_initialized, price_unit, require_admin, require_initialized,
+    set_admin, set_cash_reserves, set_etf_market_value, set_fees, set_price_decimals,
     set_token_usdc, set_token_xusg, set_total_xusg,
 };
 
-use crate::storage::{add_to_cash_reserves, get_balance_usdc, subtract_from_cash_reserves};
-use token::create_contract;
-use utils::{require_positive, require_strictly_positive};
+use crate::storage::{
+    add_to_cash_reserves, bump_instance, bump_record, get_balance, subtract_from_cash_reserves,
+    RecordKey,
+};
+use admin::{
+    get_pending_admin, remove_pending_admin, require_pending_admin, set_pending_admin, PendingAdmin,
+};
+use allowlist::{
+    get_investor, is_allowlist_enabled, remove_investor, require_allowlisted,
+    set_allowlist_enabled, set_investor, AllowlistEntry, Investor,
+};
+use attestation::{
+    get_attestation_count, get_attestations, get_latest_attestation, get_reserve_ratio_floor,
+    push_attestation, reserve_ratio, set_reserve_ratio_floor, Attestation, ReserveRatio,
+};
+use collateral::{
+    add_to_asset_reserves, base_collateral, get_asset_reserves, get_collateral,
+    get_collateral_assets, get_collateral_heartbeat, remove_collateral, revalue_collateral,
+    set_collateral, set_collateral_heartbeat, subtract_from_asset_reserves, Collateral,
+    ConversionRate, Converter, MAX_COLLATERAL_DECIMALS,
+};
+use custodian::{
+    get_nav_nonce, get_nav_signers, nav_message, require_signed, require_valid_signers,
+    set_nav_nonce, set_nav_signers, NavPayload, NavSignature, NavSigners,
+};
+use events::{
+    AdminWithdrawEvent, CashReservesEvent, EtfMarketValueEvent, FeesCollectedEvent,
+    InitializedEvent, ManagementFeeEvent, ReserveRatioFloorEvent,
+};
+use fees::{
+    accrued_fees, calculate_fee, get_management_fee_bps, get_transaction_fees, gross_for_net,
+    set_fees_accrued_at, set_management_fee_bps, set_transaction_fees, settle_fees,
+    TransactionFees, BPS_DENOMINATOR,
+};
+use limits::{
+    get_limits, get_liquidity_buffer, record_redemption, remaining_deposit_capacity,
+    remaining_redemption_capacity, require_deposit_within_limits, required_liquidity, set_limits,
+    set_liquidity_buffer, Limits, LiquidityBuffer,
+};
+use nav::{get_nav_at, get_nav_history, get_nav_history_len, push_nav_snapshot, NavSnapshot};
+use oracle::{
+    get_nav_heartbeat, get_nav_updated_at, get_oracle, get_oracle_max_deviation, remove_oracle,
+    set_nav_heartbeat, set_nav_updated_at, set_oracle, set_oracle_max_deviation, Asset,
+    OracleConfig, PriceOracleClient,
+};
+use pause::{is_paused, require_not_paused, set_paused, Operation};
+use receipt::{DepositReceipt, WithdrawReceipt};
+use redemption::{
+    get_next_redemption_id, get_queued_redemptions, get_redemption, get_redemption_head,
+    get_redemption_price_mode, push_redemption, remove_redemption, set_redemption_head,
+    set_redemption_price_mode, QueuedRedemptions, RedemptionPriceMode, RedemptionRequest,
+};
+use roles::{grant_role, has_role, require_role, revoke_role, role_members, transfer_roles, Role};
+use soroban_sdk::Vec;
+use timelock::{
+    get_scheduled_operation, get_timelock_delay, get_timelock_queue, operation_hash,
+    push_scheduled_operation, require_no_timelock, set_timelock_delay, take_scheduled_operation,
+    AdminWithdrawal, CollateralChange, FeeCollection, ScheduledOperation, TimelockCall,
+};
+use token::{create_contract, ShareToken};
+use upgrade::{
+    get_schema_version, get_stored_admin, migrate, require_stored_admin, set_schema_version,
+    SCHEMA_VERSION,
+};
+use utils::{
+    checked_add, checked_sub, mul_div, mul_div_ceil, pow10, require_positive,
+    require_strictly_positive,
+};
 
-fn burn_xusg(e: &Env, amount: i128) {
-    let total = get_total_xusg(e);
+fn burn_xusg(e: &Env, amount: i128) -> Result<(), ExcellarError> {
+    let total = checked_sub(get_total_xusg(e), amount)?;
     let xusg_contract = get_token_xusg(e);
 
     token::Client::new(e, &xusg_contract).burn(&e.current_contract_address(), &amount);
-    set_total_xusg(e, total - amount);
+    set_total_xusg(e, total);
+    Ok(())
 }
 
-fn mint_xusg(e: &Env, to: Address, amount: i128) {
-    let total = get_total_xusg(e);
+fn mint_xusg(e: &Env, to: Address, amount: i128) -> Result<(), ExcellarError> {
+    let total = checked_add(get_total_xusg(e), amount)?;
     let xusg_contract_id = get_token_xusg(e);
 
     token::Client::new(e, &xusg_contract_id).mint(&to, &amount);
 
-    set_total_xusg(e, total + amount);
+    set_total_xusg(e, total);
+    Ok(())
 }
 
 fn transfer(e: &Env, token: Address, to: Address, amount: i128) {
@@ -38,186 +124,2068 @@ fn transfer_usdc(e: &Env, to: Address, amount: i128) {
     transfer(e, get_token_usdc(e), to, amount);
 }
 
-fn calculate_xusg_price(e: &Env) -> i128 {
-    let cash_reserves = get_cash_reserves(e);
-    let fees = get_fees(e);
-    let etf_market_value = get_etf_market_value(e);
-    let total_xusg = get_total_xusg(e);
+/// Largest supported price precision; 10^18 still leaves ample headroom in an
+/// i128 for the mul-div in `deposit` and `withdraw`.
+const MAX_PRICE_DECIMALS: u32 = 18;
 
+/// Net asset value of the fund in USDC, net of the management fee accrued
+/// up to the current ledger.
+fn calculate_total_assets(e: &Env) -> Result<i128, ExcellarError> {
+    checked_sub(
+        checked_add(get_etf_market_value(e), get_cash_reserves(e))?,
+        accrued_fees(e)?,
+    )
+}
+
+/// Guards reporter updates against marking the fund at a price of zero or
+/// below.
+fn require_positive_price(e: &Env) -> Result<(), ExcellarError> {
+    calculate_xusg_price(e)?;
+    Ok(())
+}
+
+/// Price of one XUSG in USDC, scaled by `10^price_decimals`. XUSG starts at
+/// par; once it is outstanding, a fund worth nothing or less has no price
+/// and fails with `PriceNotPositive`.
+fn calculate_xusg_price(e: &Env) -> Result<i128, ExcellarError> {
+    let unit = price_unit(e);
+    let total_xusg = get_total_xusg(e);
     if total_xusg <= 0 {
-        return 1;
+        return Ok(unit);
     }
 
-    if etf_market_value + cash_reserves - fees == 0 {
-        return 1;
+    let price = mul_div(calculate_total_assets(e)?, unit, total_xusg)?;
+    if price <= 0 {
+        return Err(ExcellarError::PriceNotPositive);
     }
+    Ok(price)
+}
 
-    (etf_market_value + cash_reserves - fees) / total_xusg
+/// USDC value of the XUSG held by `account`.
+fn position_value(e: &Env, account: &Address, xusg_price: i128) -> Result<i128, ExcellarError> {
+    let balance = token::Client::new(e, &get_token_xusg(e)).balance(account);
+    mul_div(balance, xusg_price, price_unit(e))
 }
 
-// Metadata that is added on to the WASM custom section
-contractmeta!(key = "Description", val = "Money market product tokenizer");
+/// The latest attestation against the current USDC value of all XUSG.
+fn current_reserve_ratio(e: &Env) -> Result<ReserveRatio, ExcellarError> {
+    let attestation = get_latest_attestation(e).ok_or(ExcellarError::NoAttestation)?;
+    let liabilities = mul_div(get_total_xusg(e), calculate_xusg_price(e)?, price_unit(e))?;
+    Ok(reserve_ratio(e, attestation.custody_total, liabilities))
+}
 
-pub trait ExcellarTokenizerTrait {
-    fn initialize(e: Env, token_wasm_hash: BytesN<32>, token_usdc: Address, admin: Address);
+/// Splits `gross` units of `asset` into the subscription fee and the XUSG
+/// the rest buys. The minimum fee is set in USDC and charged in `asset`.
+fn deposit_receipt(
+    e: &Env,
+    asset: Address,
+    gross: i128,
+    xusg_price: i128,
+) -> Result<DepositReceipt, ExcellarError> {
+    let converter = Converter::new(e, &asset)?;
+    let fee = match get_transaction_fees(e) {
+        Some(fees) => calculate_fee(
+            gross,
+            fees.subscription_bps,
+            converter.to_asset(fees.min_fee)?,
+        )?,
+        None => 0,
+    };
+    let net = gross - fee;
+    let value = converter.to_base(net)?;
+
+    Ok(DepositReceipt {
+        asset,
+        gross,
+        fee,
+        net,
+        value,
+        xusg: mul_div(value, price_unit(e), xusg_price)?,
+        price: xusg_price,
+    })
+}
 
-    fn xusg_id(e: Env) -> Address;
+/// Values `xusg` at `xusg_price` and splits it into the redemption fee and
+/// the units of `asset` paid to the investor.
+fn withdraw_receipt(
+    e: &Env,
+    asset: Address,
+    xusg: i128,
+    xusg_price: i128,
+) -> Result<WithdrawReceipt, ExcellarError> {
+    let converter = Converter::new(e, &asset)?;
+    let value = mul_div(xusg, xusg_price, price_unit(e))?;
+    let gross = converter.to_asset(value)?;
+    let fee = match get_transaction_fees(e) {
+        Some(fees) => calculate_fee(
+            gross,
+            fees.redemption_bps,
+            converter.to_asset(fees.min_fee)?,
+        )?,
+        None => 0,
+    };
+
+    Ok(WithdrawReceipt {
+        asset,
+        xusg,
+        price: xusg_price,
+        value,
+        gross,
+        fee,
+        net: gross - fee,
+    })
+}
+
+fn pay_transaction_fee(e: &Env, asset: Address, fee: i128) {
+    if fee > 0 {
+        let recipient = get_transaction_fees(e).unwrap().recipient;
+        transfer(e, asset, recipient, fee);
+    }
+}
 
-    fn etf_market_value(e: Env) -> i128;
+/// Burns the escrowed XUSG of a withdrawal and pays out its collateral.
+fn settle_withdrawal(e: &Env, to: Address, receipt: &WithdrawReceipt) -> Result<(), ExcellarError> {
+    subtract_from_cash_reserves(e, receipt.value)?;
+    subtract_from_asset_reserves(e, &receipt.asset, receipt.gross);
+    burn_xusg(e, receipt.xusg)?;
 
-    fn set_etf_market_value(e: Env, price: i128);
+    pay_transaction_fee(e, receipt.asset.clone(), receipt.fee);
+    transfer(e, receipt.asset.clone(), to, receipt.net);
+    Ok(())
+}
 
-    fn cash_reserves(e: Env) -> i128;
+/// Fails once the ledger has moved past `deadline`, a timestamp in seconds.
+fn require_before_deadline(e: &Env, deadline: u64) -> Result<(), ExcellarError> {
+    if e.ledger().timestamp() > deadline {
+        return Err(ExcellarError::DeadlineExpired);
+    }
+    Ok(())
+}
 
-    fn set_cash_reserves(e: Env, amount: i128);
+/// USDC, fee included, that `mint` takes for `xusg_amount` at `xusg_price`,
+/// rounded up so that at least `xusg_amount` is minted.
+fn mint_cost(e: &Env, xusg_amount: i128, xusg_price: i128) -> Result<i128, ExcellarError> {
+    let net = mul_div_ceil(xusg_amount, xusg_price, price_unit(e))?;
+    match get_transaction_fees(e) {
+        Some(fees) => gross_for_net(net, fees.subscription_bps, fees.min_fee),
+        None => Ok(net),
+    }
+}
 
-    fn fees(e: Env) -> i128;
+/// XUSG that `redeem` burns to pay out `usdc_amount` after the redemption
+/// fee at `xusg_price`, rounded up so that at least `usdc_amount` is paid.
+fn redeem_cost(e: &Env, usdc_amount: i128, xusg_price: i128) -> Result<i128, ExcellarError> {
+    let gross = match get_transaction_fees(e) {
+        Some(fees) => gross_for_net(usdc_amount, fees.redemption_bps, fees.min_fee)?,
+        None => usdc_amount,
+    };
+    mul_div_ceil(gross, price_unit(e), xusg_price)
+}
 
-    fn set_fees(e: Env, amount: i128);
+/// How a deposit collects the asset from the payer.
+enum Funding {
+    /// `from` signs a transfer to the contract.
+    Transfer,
+    /// The contract spends an allowance `from` approved it for.
+    Allowance,
+}
 
-    fn deposit(e: Env, to: Address, usdc_amount: i128) -> Result<i128, ExcellarError>;
+/// Takes `amount` of `asset` from `from` and mints XUSG to `to`, failing
+/// with `SlippageExceeded` if that comes to less than `min_xusg_out`.
+fn process_deposit(
+    e: &Env,
+    funding: Funding,
+    from: Address,
+    to: Address,
+    asset: Address,
+    amount: i128,
+    min_xusg_out: i128,
+) -> Result<DepositReceipt, ExcellarError> {
+    require_not_paused(e, Operation::Deposit)?;
+    require_allowlisted(e, &to)?;
 
-    fn withdraw(e: Env, to: Address, xusg_amount: i128) -> Result<i128, ExcellarError>;
+    let zero = 0;
+    if amount <= zero {
+        return Err(ExcellarError::DepositMustBePositive);
+    }
+    let xusg_price = fresh_xusg_price(e)?;
+    if asset != get_token_usdc(e) {
+        // Records the rate the deposit is counted at when nothing was held
+        // in the asset before.
+        revalue_collateral(e, &asset)?;
+    }
+    let receipt = deposit_receipt(e, asset.clone(), amount, xusg_price)?;
+    if receipt.net <= 0 || receipt.value <= 0 {
+        return Err(ExcellarError::FeeExceedsAmount);
+    }
+    if receipt.xusg < min_xusg_out {
+        return Err(ExcellarError::SlippageExceeded);
+    }
+    require_deposit_within_limits(
+        e,
+        calculate_total_assets(e)?,
+        position_value(e, &to, xusg_price)?,
+        receipt.value,
+    )?;
 
-    fn withdraw_admin(e: Env, to: Address, usdc_amount: i128) -> Result<i128, ExcellarError>;
+    let asset_token = token::Client::new(e, &asset);
+    let contract = e.current_contract_address();
+    match funding {
+        Funding::Transfer => asset_token.transfer(&from, &contract, &amount),
+        Funding::Allowance => {
+            if asset_token.allowance(&from, &contract) < amount {
+                return Err(ExcellarError::InsufficientAllowance);
+            }
+            asset_token.transfer_from(&contract, &from, &contract, &amount)
+        }
+    }
 
-    fn balance(e: Env, account: Address) -> i128;
+    pay_transaction_fee(e, asset.clone(), receipt.fee);
+    add_to_cash_reserves(e, receipt.value)?;
+    add_to_asset_reserves(e, &asset, receipt.net)?;
 
-    fn price(e: Env) -> i128;
+    mint_xusg(e, to.clone(), receipt.xusg)?;
 
-    fn total(e: Env) -> i128;
+    events::deposit(e, to, receipt.clone());
+    Ok(receipt)
 }
 
-#[contract]
-pub struct ExcellarTokenizer;
+/// Burns `xusg_amount` of the XUSG held by `owner` and pays it out to `to`
+/// in `asset`, failing with `SlippageExceeded` if the net payout is below
+/// `min_amount_out`.
+fn process_withdraw(
+    e: &Env,
+    owner: Address,
+    to: Address,
+    asset: Address,
+    xusg_amount: i128,
+    min_amount_out: i128,
+) -> Result<WithdrawReceipt, ExcellarError> {
+    require_not_paused(e, Operation::Withdraw)?;
+    require_allowlisted(e, &owner)?;
+    let xusg_token = token::Client::new(e, &get_token_xusg(e));
 
-#[contractimpl]
-impl ExcellarTokenizerTrait for ExcellarTokenizer {
-    fn initialize(e: Env, token_wasm_hash: BytesN<32>, token_usdc: Address, admin: Address) {
-        let xusg_contract = create_contract(&e, token_wasm_hash, &token_usdc);
-        token::Client::new(&e, &xusg_contract).initialize(
-            &e.current_contract_address(),
-            &7,
-            &"Excellar Mint".into_val(&e),
-            &"XUSG".into_val(&e),
+    if xusg_amount <= 0 {
+        return Err(ExcellarError::WithdrawalMustBePositive);
+    }
+
+    if xusg_amount > xusg_token.balance(&owner) {
+        return Err(ExcellarError::InsufficientBalance);
+    }
+
+    xusg_token.transfer(&owner, &e.current_contract_address(), &xusg_amount);
+
+    let xusg_price = fresh_xusg_price(e)?;
+    let receipt = withdraw_receipt(e, asset.clone(), xusg_amount, xusg_price)?;
+    if receipt.net <= 0 {
+        return Err(ExcellarError::FeeExceedsAmount);
+    }
+    if receipt.net < min_amount_out {
+        return Err(ExcellarError::SlippageExceeded);
+    }
+
+    // Queued requests are paid first; only what they are not owed is
+    // available to direct withdrawals.
+    if receipt.gross > available_balance(e, &asset)? {
+        return Err(ExcellarError::InsufficientLiquidity);
+    }
+    record_redemption(e, owner.clone(), receipt.value)?;
+
+    settle_withdrawal(e, to, &receipt)?;
+
+    events::withdraw(e, owner, receipt.clone());
+    Ok(receipt)
+}
+
+/// Fails with `OracleDeviationExceeded` if `value` moves further from the
+/// stored ETF market value than the configured bound.
+fn require_within_deviation(e: &Env, value: i128) -> Result<(), ExcellarError> {
+    let max_bps = get_oracle_max_deviation(e);
+    let stored = get_etf_market_value(e);
+    if max_bps == 0 || stored <= 0 {
+        return Ok(());
+    }
+
+    let deviation = (value - stored).abs();
+    if mul_div(deviation, BPS_DENOMINATOR, stored)? > i128::from(max_bps) {
+        return Err(ExcellarError::OracleDeviationExceeded);
+    }
+    Ok(())
+}
+
+/// Reads the ETF market value from the configured oracle and stores it in
+/// USDC units. The oracle's own timestamp becomes the NAV update time. A
+/// reading older than the current NAV, such as one that predates a manual
+/// update, is skipped and the stored value returned.
+fn sync_etf_market_value(e: &Env, config: &OracleConfig) -> Result<i128, ExcellarError> {
+    settle_fees(e)?;
+
+    let oracle = PriceOracleClient::new(e, &config.oracle);
+    let price_data = oracle
+        .lastprice(&config.asset)
+        .ok_or(ExcellarError::OraclePriceUnavailable)?;
+    if price_data.price <= 0 {
+        return Err(ExcellarError::OraclePriceUnavailable);
+    }
+    let nav_updated_at = get_nav_updated_at(e);
+    if price_data.timestamp < nav_updated_at {
+        return Ok(get_etf_market_value(e));
+    }
+
+    let usdc_decimals = token::Client::new(e, &get_token_usdc(e)).decimals();
+    let value = mul_div(
+        price_data.price,
+        pow10(usdc_decimals)?,
+        pow10(oracle.decimals())?,
+    )?;
+    require_within_deviation(e, value)?;
+
+    set_etf_market_value(e, value);
+    set_nav_updated_at(e, price_data.timestamp);
+    // Every trade re-reads the oracle; only a new reading goes into the
+    // history.
+    if price_data.timestamp > nav_updated_at {
+        record_nav(e)?;
+        events::etf_market_value(
+            e,
+            config.oracle.clone(),
+            EtfMarketValueEvent {
+                value,
+                updated_at: price_data.timestamp,
+            },
         );
+    }
+    Ok(value)
+}
 
-        set_token_usdc(&e, token_usdc);
-        set_token_xusg(&e, xusg_contract);
-        set_total_xusg(&e, 0);
-        set_cash_reserves(&e, 0);
-        set_fees(&e, 0);
-        set_etf_market_value(&e, 0);
-        set_admin(&e, admin);
+/// Appends the NAV as it stands after an update to the history.
+fn record_nav(e: &Env) -> Result<(), ExcellarError> {
+    push_nav_snapshot(
+        e,
+        &NavSnapshot {
+            ledger: e.ledger().sequence(),
+            timestamp: e.ledger().timestamp(),
+            etf_market_value: get_etf_market_value(e),
+            cash_reserves: get_cash_reserves(e),
+            fees: accrued_fees(e)?,
+            total_xusg: get_total_xusg(e),
+            price: calculate_xusg_price(e)?,
+        },
+    );
+    Ok(())
+}
+
+/// Re-marks the cash reserves held in collateral other than USDC whose rate
+/// has moved since they were last counted.
+fn revalue_reserves(e: &Env) -> Result<(), ExcellarError> {
+    let token_usdc = get_token_usdc(e);
+    for asset in get_collateral_assets(e).iter() {
+        if asset == token_usdc || get_asset_reserves(e, &asset) <= 0 {
+            continue;
+        }
+        let change = revalue_collateral(e, &asset)?;
+        if change != 0 {
+            let previous = get_cash_reserves(e);
+            let amount = checked_add(previous, change)?;
+            set_cash_reserves(e, amount);
+            events::collateral_revalued(e, asset, CashReservesEvent { previous, amount });
+        }
     }
+    Ok(())
+}
 
-    fn xusg_id(e: Env) -> Address {
-        get_token_xusg(&e)
+/// The XUSG price to trade at, refreshed from the oracle when one is
+/// configured and refused when the ETF market value is past its heartbeat.
+/// Collateral held in other assets is re-marked at its current rate first.
+fn fresh_xusg_price(e: &Env) -> Result<i128, ExcellarError> {
+    settle_fees(e)?;
+    if let Some(config) = get_oracle(e) {
+        sync_etf_market_value(e, &config)?;
     }
+    revalue_reserves(e)?;
 
-    fn etf_market_value(e: Env) -> i128 {
-        get_etf_market_value(&e)
+    let heartbeat = get_nav_heartbeat(e);
+    if heartbeat > 0 && e.ledger().timestamp() > get_nav_updated_at(e).saturating_add(heartbeat) {
+        return Err(ExcellarError::StalePrice);
     }
 
-    fn set_etf_market_value(e: Env, value: i128) {
-        require_admin(&e);
-        require_strictly_positive(value);
-        set_etf_market_value(&e, value);
-        require_strictly_positive(calculate_xusg_price(&e));
+    calculate_xusg_price(e)
+}
+
+fn apply_management_fee(e: &Env, bps: u32) -> Result<(), ExcellarError> {
+    if i128::from(bps) > BPS_DENOMINATOR {
+        return Err(ExcellarError::InvalidFeeRate);
     }
 
-    fn cash_reserves(e: Env) -> i128 {
-        get_cash_reserves(&e)
+    // Fees up to now accrue at the old rate.
+    settle_fees(e)?;
+    let previous_bps = get_management_fee_bps(e);
+    set_management_fee_bps(e, bps);
+
+    events::management_fee(e, ManagementFeeEvent { previous_bps, bps });
+    Ok(())
+}
+
+fn apply_transaction_fees(e: &Env, fees: TransactionFees) -> Result<(), ExcellarError> {
+    if i128::from(fees.subscription_bps) > BPS_DENOMINATOR
+        || i128::from(fees.redemption_bps) > BPS_DENOMINATOR
+        || fees.min_fee < 0
+    {
+        return Err(ExcellarError::InvalidFeeRate);
     }
 
-    fn set_cash_reserves(e: Env, amount: i128) {
-        require_admin(&e);
-        require_positive(amount);
-        set_cash_reserves(&e, amount);
-        require_strictly_positive(calculate_xusg_price(&e));
+    set_transaction_fees(e, &fees);
+
+    events::transaction_fees(e, fees);
+    Ok(())
+}
+
+fn apply_propose_admin(e: &Env, pending: PendingAdmin) {
+    set_pending_admin(e, &pending);
+    events::admin_proposed(e, get_admin(e), pending);
+}
+
+/// Units of `asset` the queued redemptions paid in it are owed, valuing
+/// those priced at fulfilment at the current price.
+fn queued_liability(e: &Env, asset: &Address) -> Result<i128, ExcellarError> {
+    let queued = get_queued_redemptions(e, asset);
+    let mut value = queued.value;
+    if queued.xusg > 0 {
+        let unlocked = mul_div(queued.xusg, calculate_xusg_price(e)?, price_unit(e))?;
+        value = checked_add(value, unlocked)?;
     }
+    if value <= 0 {
+        return Ok(0);
+    }
+    Converter::new(e, asset)?.to_asset(value)
+}
 
-    fn fees(e: Env) -> i128 {
-        get_fees(&e)
+/// The contract's balance of `asset` less what the redemption queue is owed
+/// in it.
+fn available_balance(e: &Env, asset: &Address) -> Result<i128, ExcellarError> {
+    checked_sub(get_balance(e, asset.clone()), queued_liability(e, asset)?)
+}
+
+/// How much of `asset` a treasurer can move out without taking what the
+/// redemption queue is owed or eating into the USDC liquidity buffer.
+fn admin_withdrawable(e: &Env, asset: &Address) -> Result<i128, ExcellarError> {
+    let available = available_balance(e, asset)?;
+    if *asset != get_token_usdc(e) {
+        return Ok(available.max(0));
     }
 
-    fn set_fees(e: Env, amount: i128) {
-        require_admin(&e);
-        require_positive(amount);
-        set_fees(&e, amount);
-        require_strictly_positive(calculate_xusg_price(&e));
+    let buffer = required_liquidity(e, calculate_total_assets(e)?)?;
+    Ok(available.saturating_sub(buffer).max(0))
+}
+
+fn apply_admin_withdrawal(e: &Env, withdrawal: AdminWithdrawal) -> Result<i128, ExcellarError> {
+    let AdminWithdrawal {
+        treasurer,
+        to,
+        asset,
+        amount,
+    } = withdrawal;
+    require_not_paused(e, Operation::AdminWithdraw)?;
+    let zero = 0;
+    if amount <= zero {
+        return Err(ExcellarError::WithdrawalMustBePositive);
+    }
+    if get_collateral(e, &asset).is_none() {
+        return Err(ExcellarError::UnsupportedCollateral);
     }
 
-    fn balance(e: Env, account: Address) -> i128 {
-        let xusg_contract = get_token_xusg(&e);
-        token::Client::new(&e, &xusg_contract).balance(&account)
+    let balance_cash = get_balance(e, asset.clone());
+    if amount > balance_cash {
+        return Err(ExcellarError::InsufficientBalance);
+    }
+    if amount > available_balance(e, &asset)? {
+        return Err(ExcellarError::InsufficientLiquidity);
+    }
+    if amount > admin_withdrawable(e, &asset)? {
+        return Err(ExcellarError::LiquidityBufferBreached);
     }
 
-    fn price(e: Env) -> i128 {
-        calculate_xusg_price(&e)
+    transfer(e, asset.clone(), to.clone(), amount);
+    subtract_from_asset_reserves(e, &asset, amount);
+
+    events::admin_withdraw(e, treasurer, AdminWithdrawEvent { to, asset, amount });
+    Ok(amount)
+}
+
+fn apply_timelock_delay(e: &Env, seconds: u64) -> Result<(), GovernanceError> {
+    set_timelock_delay(e, seconds)?;
+    events::timelock_delay(e, seconds);
+    Ok(())
+}
+
+fn apply_upgrade(e: &Env, new_wasm_hash: BytesN<32>) {
+    e.deployer()
+        .update_current_contract_wasm(new_wasm_hash.clone());
+    events::upgraded(e, new_wasm_hash);
+}
+
+fn apply_fee_collection(e: &Env, collection: FeeCollection) -> Result<i128, ExcellarError> {
+    let FeeCollection { treasurer, to } = collection;
+    require_not_paused(e, Operation::AdminWithdraw)?;
+
+    let fees = settle_fees(e)?;
+    if fees <= 0 {
+        return Ok(0);
     }
 
-    fn total(e: Env) -> i128 {
-        get_total_xusg(&e)
+    if fees > available_balance(e, &get_token_usdc(e))? {
+        return Err(ExcellarError::InsufficientLiquidity);
+    }
+    if fees > admin_withdrawable(e, &get_token_usdc(e))? {
+        return Err(ExcellarError::LiquidityBufferBreached);
     }
 
-    fn deposit(e: Env, to: Address, usdc_deposit: i128) -> Result<i128, ExcellarError> {
-        to.require_auth();
+    subtract_from_cash_reserves(e, fees)?;
+    subtract_from_asset_reserves(e, &get_token_usdc(e), fees);
+    set_fees(e, 0);
+    transfer_usdc(e, to.clone(), fees);
 
-        let zero = 0;
-        if usdc_deposit <= zero {
-            return Err(ExcellarError::DepositMustBePositive);
+    events::fees_collected(e, treasurer, FeesCollectedEvent { to, amount: fees });
+    Ok(fees)
+}
+
+fn apply_collateral(e: &Env, change: CollateralChange) -> Result<(), ExcellarError> {
+    let CollateralChange { asset, collateral } = change;
+    // USDC is the unit everything else is valued in.
+    if asset == get_token_usdc(e) || collateral.decimals > MAX_COLLATERAL_DECIMALS {
+        return Err(ExcellarError::InvalidCollateral);
+    }
+    if let ConversionRate::Fixed(rate) = collateral.rate {
+        if rate <= 0 {
+            return Err(ExcellarError::InvalidCollateral);
         }
-        let token_usdc_token = token::Client::new(&e, &get_token_usdc(&e));
-        token_usdc_token.transfer(&to, &e.current_contract_address(), &usdc_deposit);
+    }
 
-        let xusg_price = calculate_xusg_price(&e);
-        let xusg_issued = usdc_deposit / xusg_price;
-        add_to_cash_reserves(&e, usdc_deposit);
+    set_collateral(e, asset.clone(), &collateral);
 
-        mint_xusg(&e, to, xusg_issued);
+    events::collateral_set(e, asset, collateral);
+    Ok(())
+}
 
-        Ok(xusg_issued)
+fn apply_collateral_removal(e: &Env, asset: Address) -> Result<(), ExcellarError> {
+    if asset == get_token_usdc(e) {
+        return Err(ExcellarError::InvalidCollateral);
+    }
+    if get_collateral(e, &asset).is_none() {
+        return Err(ExcellarError::UnsupportedCollateral);
+    }
+    // Reserves in a delisted asset could no longer be withdrawn, nor could
+    // the requests queued for it be paid in it.
+    if get_asset_reserves(e, &asset) > 0
+        || get_queued_redemptions(e, &asset) != QueuedRedemptions::default()
+    {
+        return Err(ExcellarError::CollateralFunded);
     }
 
-    fn withdraw(e: Env, to: Address, xusg_amount: i128) -> Result<i128, ExcellarError> {
-        to.require_auth();
-        let token_usdc_token = token::Client::new(&e, &get_token_xusg(&e));
+    remove_collateral(e, &asset);
 
-        if xusg_amount <= 0 {
-            return Err(ExcellarError::WithdrawalMustBePositive);
-        }
+    events::collateral_removed(e, asset);
+    Ok(())
+}
 
-        if xusg_amount > token_usdc_token.balance(&to) {
-            return Err(ExcellarError::InsufficientBalance);
-        }
+fn apply_collateral_heartbeat(e: &Env, seconds: u64) {
+    set_collateral_heartbeat(e, seconds);
+    events::collateral_heartbeat(e, seconds);
+}
+
+fn apply_oracle(e: &Env, config: OracleConfig) {
+    set_oracle(e, &config);
+    events::oracle_set(e, config);
+}
 
-        token_usdc_token.transfer(&to, &e.current_contract_address(), &xusg_amount);
+fn apply_oracle_removal(e: &Env) {
+    remove_oracle(e);
+    events::oracle_removed(e);
+}
 
-        let xusg_price = calculate_xusg_price(&e);
-        let out_usdc = xusg_amount * xusg_price;
+fn apply_oracle_max_deviation(e: &Env, bps: u32) {
+    set_oracle_max_deviation(e, bps);
+    events::oracle_max_deviation(e, bps);
+}
 
-        subtract_from_cash_reserves(&e, out_usdc);
-        burn_xusg(&e, xusg_amount);
+fn apply_nav_heartbeat(e: &Env, seconds: u64) {
+    set_nav_heartbeat(e, seconds);
+    events::nav_heartbeat(e, seconds);
+}
 
-        transfer_usdc(&e, to, out_usdc);
+fn apply_nav_signers(e: &Env, signers: NavSigners) -> Result<(), ExcellarError> {
+    require_valid_signers(&signers)?;
+    set_nav_signers(e, &signers);
 
-        Ok(out_usdc)
-    }
+    events::nav_signers(e, signers);
+    Ok(())
+}
 
-    fn withdraw_admin(e: Env, to: Address, usdc_amount: i128) -> Result<i128, ExcellarError> {
-        require_admin(&e);
-        let zero = 0;
-        if usdc_amount <= zero {
-            return Err(ExcellarError::WithdrawalMustBePositive);
+fn apply_limits(e: &Env, limits: Limits) {
+    set_limits(e, &limits);
+    events::limits(e, limits);
+}
+
+fn apply_liquidity_buffer(e: &Env, buffer: LiquidityBuffer) -> Result<(), ExcellarError> {
+    set_liquidity_buffer(e, &buffer)?;
+    events::liquidity_buffer(e, buffer);
+    Ok(())
+}
+
+fn apply_redemption_price_mode(e: &Env, mode: RedemptionPriceMode) {
+    set_redemption_price_mode(e, mode);
+    events::redemption_price_mode(e, mode);
+}
+
+/// Checks that `caller` may schedule `call`: the treasurer named in a
+/// withdrawal or fee collection, the admin for everything else.
+fn require_scheduler(e: &Env, call: &TimelockCall) -> Result<(), AccessError> {
+    match call {
+        TimelockCall::WithdrawAdmin(withdrawal) => {
+            require_role(e, Role::Treasurer, &withdrawal.treasurer)
+        }
+        TimelockCall::CollectFees(collection) => {
+            require_role(e, Role::Treasurer, &collection.treasurer)
         }
+        _ => require_admin(e),
+    }
+}
 
-        let balance_cash = get_balance_usdc(&e);
-        if usdc_amount > balance_cash {
-            return Err(ExcellarError::InsufficientBalance);
+/// Fails if `treasurer` lost the role while its operation waited in the
+/// queue.
+fn require_still_treasurer(e: &Env, treasurer: &Address) -> Result<(), ExcellarError> {
+    if !has_role(e, Role::Treasurer, treasurer) {
+        return Err(ExcellarError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Makes a scheduled call, failing with the error the direct call would have
+/// returned.
+fn apply_timelock_call(e: &Env, call: TimelockCall) -> Result<(), Error> {
+    match call {
+        TimelockCall::WithdrawAdmin(withdrawal) => {
+            require_still_treasurer(e, &withdrawal.treasurer)?;
+            apply_admin_withdrawal(e, withdrawal)?;
         }
+        TimelockCall::SetManagementFee(bps) => apply_management_fee(e, bps)?,
+        TimelockCall::SetTransactionFees(fees) => apply_transaction_fees(e, fees)?,
+        TimelockCall::ProposeAdmin(pending) => apply_propose_admin(e, pending),
+        TimelockCall::SetTimelockDelay(seconds) => apply_timelock_delay(e, seconds)?,
+        TimelockCall::Upgrade(new_wasm_hash) => apply_upgrade(e, new_wasm_hash),
+        TimelockCall::CollectFees(collection) => {
+            require_still_treasurer(e, &collection.treasurer)?;
+            apply_fee_collection(e, collection)?;
+        }
+        TimelockCall::GrantRole(change) => grant_role(e, change.role, change.account),
+        TimelockCall::RevokeRole(change) => revoke_role(e, change.role, change.account),
+        TimelockCall::SetCollateral(change) => apply_collateral(e, change)?,
+        TimelockCall::RemoveCollateral(asset) => apply_collateral_removal(e, asset)?,
+        TimelockCall::SetCollateralHeartbeat(seconds) => apply_collateral_heartbeat(e, seconds),
+        TimelockCall::SetOracle(config) => apply_oracle(e, config),
+        TimelockCall::RemoveOracle => apply_oracle_removal(e),
+        TimelockCall::SetOracleMaxDeviation(bps) => apply_oracle_max_deviation(e, bps),
+        TimelockCall::SetNavHeartbeat(seconds) => apply_nav_heartbeat(e, seconds),
+        TimelockCall::SetNavSigners(signers) => apply_nav_signers(e, signers)?,
+        TimelockCall::SetLimits(limits) => apply_limits(e, limits),
+        TimelockCall::SetLiquidityBuffer(buffer) => apply_liquidity_buffer(e, buffer)?,
+        TimelockCall::SetRedemptionPriceMode(mode) => apply_redemption_price_mode(e, mode),
+    }
+    Ok(())
+}
+
+/// Takes an operation out of the queue once its delay has passed.
+fn take_ready_operation(e: &Env, hash: &BytesN<32>) -> Result<ScheduledOperation, GovernanceError> {
+    require_initialized(e)?;
+    let operation = take_scheduled_operation(e, hash).ok_or(GovernanceError::OperationNotFound)?;
+    if e.ledger().timestamp() < operation.ready_at {
+        return Err(GovernanceError::OperationNotReady);
+    }
+    Ok(operation)
+}
+
+// Metadata that is added on to the WASM custom section
+contractmeta!(key = "Description", val = "Money market product tokenizer");
+
+pub trait ExcellarTokenizerTrait {
+    fn initialize(
+        e: Env,
+        token_wasm_hash: BytesN<32>,
+        token_usdc: Address,
+        admin: Address,
+        price_decimals: u32,
+        share: ShareToken,
+    ) -> Result<(), ExcellarError>;
+
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), GovernanceError>;
+
+    fn migrate(e: Env) -> Result<u32, GovernanceError>;
+
+    fn schema_version(e: Env) -> Result<u32, GovernanceError>;
+
+    fn extend_ttl(e: Env, records: Vec<RecordKey>) -> Result<(), ExcellarError>;
+
+    fn admin(e: Env) -> Result<Address, GovernanceError>;
+
+    fn pending_admin(e: Env) -> Result<Option<PendingAdmin>, GovernanceError>;
+
+    fn propose_admin(
+        e: Env,
+        new_admin: Address,
+        expires_at: Option<u64>,
+    ) -> Result<(), GovernanceError>;
+
+    fn accept_admin(e: Env) -> Result<(), GovernanceError>;
+
+    fn timelock_delay(e: Env) -> Result<u64, GovernanceError>;
+
+    fn set_timelock_delay(e: Env, seconds: u64) -> Result<(), GovernanceError>;
+
+    fn schedule(
+        e: Env,
+        call: TimelockCall,
+        salt: BytesN<32>,
+    ) -> Result<BytesN<32>, GovernanceError>;
+
+    fn execute(e: Env, hash: BytesN<32>) -> Result<(), Error>;
+
+    fn cancel(e: Env, guardian: Address, hash: BytesN<32>) -> Result<(), GovernanceError>;
+
+    fn timelock_queue(e: Env) -> Result<Vec<BytesN<32>>, GovernanceError>;
+
+    fn scheduled_operation(e: Env, hash: BytesN<32>)
+        -> Result<ScheduledOperation, GovernanceError>;
+
+    fn xusg_id(e: Env) -> Result<Address, ExcellarError>;
+
+    fn price_decimals(e: Env) -> Result<u32, ExcellarError>;
+
+    fn etf_market_value(e: Env) -> Result<i128, ExcellarError>;
+
+    fn set_etf_market_value(e: Env, reporter: Address, value: i128) -> Result<(), ExcellarError>;
+
+    fn oracle(e: Env) -> Result<Option<OracleConfig>, ExcellarError>;
+
+    fn set_oracle(e: Env, oracle: Address, asset: Asset) -> Result<(), ExcellarError>;
+
+    fn remove_oracle(e: Env) -> Result<(), ExcellarError>;
+
+    fn update_etf_market_value(e: Env) -> Result<i128, ExcellarError>;
+
+    fn oracle_max_deviation(e: Env) -> Result<u32, ExcellarError>;
+
+    fn set_oracle_max_deviation(e: Env, bps: u32) -> Result<(), ExcellarError>;
+
+    fn nav_heartbeat(e: Env) -> Result<u64, ExcellarError>;
+
+    fn nav_signers(e: Env) -> Result<Option<NavSigners>, ExcellarError>;
+
+    fn set_nav_signers(e: Env, signers: NavSigners) -> Result<(), ExcellarError>;
+
+    fn nav_nonce(e: Env) -> Result<u64, ExcellarError>;
+
+    fn signed_nav_message(e: Env, payload: NavPayload) -> Result<Bytes, ExcellarError>;
+
+    fn submit_nav(
+        e: Env,
+        payload: NavPayload,
+        signatures: Vec<NavSignature>,
+    ) -> Result<(), ExcellarError>;
+
+    fn set_nav_heartbeat(e: Env, seconds: u64) -> Result<(), ExcellarError>;
+
+    fn nav_updated_at(e: Env) -> Result<u64, ExcellarError>;
+
+    fn nav_history_len(e: Env) -> Result<u32, ExcellarError>;
+
+    fn nav_history(e: Env, start: u32, limit: u32) -> Result<Vec<NavSnapshot>, ExcellarError>;
+
+    fn nav_at(e: Env, timestamp: u64) -> Result<NavSnapshot, ExcellarError>;
+
+    fn price_at(e: Env, timestamp: u64) -> Result<i128, ExcellarError>;
+
+    fn cash_reserves(e: Env) -> Result<i128, ExcellarError>;
+
+    fn set_cash_reserves(e: Env, reporter: Address, amount: i128) -> Result<(), ExcellarError>;
+
+    fn fees(e: Env) -> Result<i128, ExcellarError>;
+
+    fn management_fee(e: Env) -> Result<u32, ExcellarError>;
+
+    fn set_management_fee(e: Env, bps: u32) -> Result<(), ExcellarError>;
+
+    fn collect_fees(e: Env, treasurer: Address, to: Address) -> Result<i128, ExcellarError>;
+
+    fn transaction_fees(e: Env) -> Result<Option<TransactionFees>, ExcellarError>;
+
+    fn set_transaction_fees(e: Env, fees: TransactionFees) -> Result<(), ExcellarError>;
+
+    fn attest(
+        e: Env,
+        auditor: Address,
+        report_hash: BytesN<32>,
+        custody_total: i128,
+        reported_at: u64,
+        uri: String,
+    ) -> Result<ReserveRatio, ExcellarError>;
+
+    fn attestation_count(e: Env) -> Result<u32, ExcellarError>;
+
+    fn attestations(e: Env, start: u32, limit: u32) -> Result<Vec<Attestation>, ExcellarError>;
+
+    fn latest_attestation(e: Env) -> Result<Attestation, ExcellarError>;
+
+    fn reserve_ratio(e: Env) -> Result<ReserveRatio, ExcellarError>;
+
+    fn reserve_ratio_floor(e: Env) -> Result<u32, ExcellarError>;
+
+    fn set_reserve_ratio_floor(e: Env, bps: u32) -> Result<(), ExcellarError>;
+
+    fn grant_role(e: Env, role: Role, account: Address) -> Result<(), GovernanceError>;
+
+    fn revoke_role(e: Env, role: Role, account: Address) -> Result<(), GovernanceError>;
+
+    fn has_role(e: Env, role: Role, account: Address) -> Result<bool, GovernanceError>;
+
+    fn role_members(e: Env, role: Role) -> Result<Vec<Address>, GovernanceError>;
+
+    fn pause(e: Env, pauser: Address, operation: Operation) -> Result<(), ExcellarError>;
+
+    fn unpause(e: Env, pauser: Address, operation: Operation) -> Result<(), ExcellarError>;
+
+    fn is_paused(e: Env, operation: Operation) -> Result<bool, ExcellarError>;
+
+    fn add_investors(
+        e: Env,
+        compliance: Address,
+        investors: Vec<Investor>,
+    ) -> Result<(), ExcellarError>;
+
+    fn remove_investors(
+        e: Env,
+        compliance: Address,
+        accounts: Vec<Address>,
+    ) -> Result<(), ExcellarError>;
+
+    fn investor(e: Env, account: Address) -> Result<Option<AllowlistEntry>, ExcellarError>;
+
+    fn is_allowlisted(e: Env, account: Address) -> Result<bool, ExcellarError>;
+
+    fn allowlist_enabled(e: Env) -> Result<bool, ExcellarError>;
+
+    fn set_allowlist_enabled(e: Env, enabled: bool) -> Result<(), ExcellarError>;
+
+    fn collateral_assets(e: Env) -> Result<Vec<Address>, ExcellarError>;
+
+    fn collateral(e: Env, asset: Address) -> Result<Option<Collateral>, ExcellarError>;
+
+    fn set_collateral(e: Env, asset: Address, collateral: Collateral) -> Result<(), ExcellarError>;
+
+    fn remove_collateral(e: Env, asset: Address) -> Result<(), ExcellarError>;
+
+    fn collateral_heartbeat(e: Env) -> Result<u64, ExcellarError>;
+
+    fn set_collateral_heartbeat(e: Env, seconds: u64) -> Result<(), ExcellarError>;
+
+    fn asset_reserves(e: Env, asset: Address) -> Result<i128, ExcellarError>;
+
+    fn deposit(
+        e: Env,
+        to: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<DepositReceipt, ExcellarError>;
+
+    fn withdraw(
+        e: Env,
+        to: Address,
+        asset: Address,
+        xusg_amount: i128,
+    ) -> Result<WithdrawReceipt, ExcellarError>;
+
+    fn deposit_checked(
+        e: Env,
+        to: Address,
+        asset: Address,
+        amount: i128,
+        min_xusg_out: i128,
+        deadline: u64,
+    ) -> Result<DepositReceipt, ExcellarError>;
+
+    fn deposit_for(
+        e: Env,
+        from: Address,
+        beneficiary: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<DepositReceipt, ExcellarError>;
+
+    fn withdraw_to(
+        e: Env,
+        owner: Address,
+        receiver: Address,
+        asset: Address,
+        xusg_amount: i128,
+    ) -> Result<WithdrawReceipt, ExcellarError>;
+
+    fn deposit_from(
+        e: Env,
+        from: Address,
+        to: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<DepositReceipt, ExcellarError>;
+
+    fn withdraw_checked(
+        e: Env,
+        to: Address,
+        asset: Address,
+        xusg_amount: i128,
+        min_amount_out: i128,
+        deadline: u64,
+    ) -> Result<WithdrawReceipt, ExcellarError>;
+
+    fn withdraw_admin(
+        e: Env,
+        treasurer: Address,
+        to: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<i128, ExcellarError>;
+
+    fn request_redeem(
+        e: Env,
+        owner: Address,
+        asset: Address,
+        xusg_amount: i128,
+    ) -> Result<u64, ExcellarError>;
+
+    fn cancel_redeem(e: Env, owner: Address, request_id: u64) -> Result<i128, ExcellarError>;
+
+    fn fulfil_redemptions(e: Env, max_requests: u32) -> Result<u32, ExcellarError>;
+
+    fn redemption(e: Env, request_id: u64) -> Result<RedemptionRequest, ExcellarError>;
+
+    fn redemption_queue_head(e: Env) -> Result<u64, ExcellarError>;
+
+    fn redemption_price_mode(e: Env) -> Result<RedemptionPriceMode, ExcellarError>;
+
+    fn set_redemption_price_mode(e: Env, mode: RedemptionPriceMode) -> Result<(), ExcellarError>;
+
+    fn limits(e: Env) -> Result<Limits, ExcellarError>;
+
+    fn set_limits(e: Env, limits: Limits) -> Result<(), ExcellarError>;
+
+    fn liquidity_buffer(e: Env) -> Result<LiquidityBuffer, ExcellarError>;
+
+    fn set_liquidity_buffer(e: Env, buffer: LiquidityBuffer) -> Result<(), ExcellarError>;
+
+    fn admin_withdrawable(e: Env, asset: Address) -> Result<i128, ExcellarError>;
+
+    fn remaining_deposit_capacity(e: Env, account: Address) -> Result<i128, ExcellarError>;
+
+    fn remaining_redemption_capacity(e: Env, account: Address) -> Result<i128, ExcellarError>;
+
+    fn balance(e: Env, account: Address) -> Result<i128, ExcellarError>;
+
+    fn asset(e: Env) -> Result<Address, ExcellarError>;
+
+    fn total_assets(e: Env) -> Result<i128, ExcellarError>;
+
+    fn convert_to_shares(e: Env, assets: i128) -> Result<i128, ExcellarError>;
+
+    fn convert_to_assets(e: Env, shares: i128) -> Result<i128, ExcellarError>;
+
+    fn max_deposit(e: Env, receiver: Address) -> Result<i128, ExcellarError>;
+
+    fn max_withdraw(e: Env, owner: Address) -> Result<i128, ExcellarError>;
+
+    fn preview_deposit(e: Env, assets: i128) -> Result<i128, ExcellarError>;
+
+    fn preview_mint(e: Env, shares: i128) -> Result<i128, ExcellarError>;
+
+    fn preview_redeem(e: Env, assets: i128) -> Result<i128, ExcellarError>;
+
+    fn mint(e: Env, to: Address, xusg_amount: i128) -> Result<DepositReceipt, ExcellarError>;
+
+    fn redeem(e: Env, to: Address, usdc_amount: i128) -> Result<WithdrawReceipt, ExcellarError>;
+
+    fn price(e: Env) -> Result<i128, ExcellarError>;
+
+    fn total(e: Env) -> Result<i128, ExcellarError>;
+}
+
+#[contract]
+pub struct ExcellarTokenizer;
+
+#[contractimpl]
+impl ExcellarTokenizerTrait for ExcellarTokenizer {
+    fn initialize(
+        e: Env,
+        token_wasm_hash: BytesN<32>,
+        token_usdc: Address,
+        admin: Address,
+        price_decimals: u32,
+        share: ShareToken,
+    ) -> Result<(), ExcellarError> {
+        if is_initialized(&e) {
+            return Err(ExcellarError::AlreadyInitialized);
+        }
+        if price_decimals > MAX_PRICE_DECIMALS {
+            return Err(ExcellarError::InvalidPriceDecimals);
+        }
+        // XUSG amounts are computed in units of the collateral, so the share
+        // token has to count them with the same decimals.
+        if share.decimals != token::Client::new(&e, &token_usdc).decimals() {
+            return Err(ExcellarError::ShareDecimalsMismatch);
+        }
+
+        let xusg_contract = create_contract(&e, token_wasm_hash, &token_usdc, &share);
+        token::Client::new(&e, &xusg_contract).initialize(
+            &e.current_contract_address(),
+            &share.decimals,
+            &share.name,
+            &share.symbol,
+        );
+
+        set_token_usdc(&e, token_usdc.clone());
+        set_collateral(&e, token_usdc.clone(), &base_collateral(&e));
+        set_token_xusg(&e, xusg_contract.clone());
+        set_total_xusg(&e, 0);
+        set_cash_reserves(&e, 0);
+        set_fees(&e, 0);
+        set_management_fee_bps(&e, 0);
+        set_fees_accrued_at(&e, e.ledger().timestamp());
+        set_etf_market_value(&e, 0);
+        set_nav_updated_at(&e, e.ledger().timestamp());
+        set_price_decimals(&e, price_decimals);
+        set_redemption_price_mode(&e, RedemptionPriceMode::AtFulfilment);
+        grant_role(&e, Role::Reporter, admin.clone());
+        grant_role(&e, Role::Treasurer, admin.clone());
+        grant_role(&e, Role::Pauser, admin.clone());
+        grant_role(&e, Role::Compliance, admin.clone());
+        set_allowlist_enabled(&e, true);
+        set_admin(&e, admin.clone());
+        set_schema_version(&e, SCHEMA_VERSION);
+        bump_instance(&e);
+
+        events::initialized(
+            &e,
+            InitializedEvent {
+                admin,
+                token_usdc,
+                token_xusg: xusg_contract,
+                price_decimals,
+            },
+        );
+        Ok(())
+    }
+
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), GovernanceError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_upgrade(&e, new_wasm_hash);
+        Ok(())
+    }
+
+    fn migrate(e: Env) -> Result<u32, GovernanceError> {
+        // Not timelocked: it can only bring the storage in line with the code
+        // that `upgrade` installed, and has to follow it without delay.
+        require_stored_admin(&e)?;
+        let from = get_schema_version(&e);
+        let to = migrate(&e)?;
+        if to != from {
+            events::migrated(&e, from, to);
+        }
+        Ok(to)
+    }
+
+    fn schema_version(e: Env) -> Result<u32, GovernanceError> {
+        get_stored_admin(&e).ok_or(GovernanceError::NotInitialized)?;
+        Ok(get_schema_version(&e))
+    }
+
+    fn extend_ttl(e: Env, records: Vec<RecordKey>) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+
+        // Reading XUSG balances extends them and the XUSG instance as well.
+        let xusg = token::Client::new(&e, &get_token_xusg(&e));
+        xusg.balance(&e.current_contract_address());
+        for key in records.iter() {
+            if !e.storage().persistent().has(&key) {
+                continue;
+            }
+
+            bump_record(&e, &key);
+            if let RecordKey::Investor(account) = key {
+                xusg.balance(&account);
+            }
+        }
+        Ok(())
+    }
+
+    fn admin(e: Env) -> Result<Address, GovernanceError> {
+        require_initialized(&e)?;
+        Ok(get_admin(&e))
+    }
+
+    fn pending_admin(e: Env) -> Result<Option<PendingAdmin>, GovernanceError> {
+        require_initialized(&e)?;
+        Ok(get_pending_admin(&e))
+    }
+
+    fn propose_admin(
+        e: Env,
+        new_admin: Address,
+        expires_at: Option<u64>,
+    ) -> Result<(), GovernanceError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_propose_admin(
+            &e,
+            PendingAdmin {
+                admin: new_admin,
+                expires_at,
+            },
+        );
+        Ok(())
+    }
+
+    fn accept_admin(e: Env) -> Result<(), GovernanceError> {
+        require_initialized(&e)?;
+        let admin = require_pending_admin(&e)?;
+        let previous = get_admin(&e);
+
+        set_admin(&e, admin.clone());
+        remove_pending_admin(&e);
+        transfer_roles(&e, &previous, &admin);
+        events::admin_changed(&e, previous, admin);
+        Ok(())
+    }
+
+    fn timelock_delay(e: Env) -> Result<u64, GovernanceError> {
+        require_initialized(&e)?;
+        Ok(get_timelock_delay(&e))
+    }
+
+    fn set_timelock_delay(e: Env, seconds: u64) -> Result<(), GovernanceError> {
+        require_admin(&e)?;
+        // Once enabled, the delay itself can only change through the timelock.
+        require_no_timelock(&e)?;
+        apply_timelock_delay(&e, seconds)
+    }
+
+    fn schedule(
+        e: Env,
+        call: TimelockCall,
+        salt: BytesN<32>,
+    ) -> Result<BytesN<32>, GovernanceError> {
+        require_initialized(&e)?;
+        require_scheduler(&e, &call)?;
+
+        let hash = operation_hash(&e, &call, &salt);
+        if get_scheduled_operation(&e, &hash).is_some() {
+            return Err(GovernanceError::OperationAlreadyScheduled);
+        }
+
+        let now = e.ledger().timestamp();
+        let operation = ScheduledOperation {
+            call,
+            scheduled_at: now,
+            ready_at: now.saturating_add(get_timelock_delay(&e)),
+        };
+        push_scheduled_operation(&e, &hash, &operation);
+
+        events::operation_scheduled(&e, hash.clone(), operation);
+        Ok(hash)
+    }
+
+    fn execute(e: Env, hash: BytesN<32>) -> Result<(), Error> {
+        let operation = take_ready_operation(&e, &hash)?;
+        apply_timelock_call(&e, operation.call.clone())?;
+
+        events::operation_executed(&e, hash, operation.call);
+        Ok(())
+    }
+
+    fn cancel(e: Env, guardian: Address, hash: BytesN<32>) -> Result<(), GovernanceError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Guardian, &guardian)?;
+        let operation =
+            get_scheduled_operation(&e, &hash).ok_or(GovernanceError::OperationNotFound)?;
+        // Otherwise a rogue guardian could keep itself, or another guardian,
+        // in place by cancelling every attempt to remove it.
+        if let TimelockCall::RevokeRole(change) = operation.call {
+            if change.role == Role::Guardian {
+                return Err(GovernanceError::OperationNotCancellable);
+            }
+        }
+        take_scheduled_operation(&e, &hash);
+
+        events::operation_cancelled(&e, guardian, hash);
+        Ok(())
+    }
+
+    fn timelock_queue(e: Env) -> Result<Vec<BytesN<32>>, GovernanceError> {
+        require_initialized(&e)?;
+        Ok(get_timelock_queue(&e))
+    }
+
+    fn scheduled_operation(
+        e: Env,
+        hash: BytesN<32>,
+    ) -> Result<ScheduledOperation, GovernanceError> {
+        require_initialized(&e)?;
+        get_scheduled_operation(&e, &hash).ok_or(GovernanceError::OperationNotFound)
+    }
+
+    fn xusg_id(e: Env) -> Result<Address, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_token_xusg(&e))
+    }
+
+    fn price_decimals(e: Env) -> Result<u32, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_price_decimals(&e))
+    }
+
+    fn etf_market_value(e: Env) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_etf_market_value(&e))
+    }
+
+    fn set_etf_market_value(e: Env, reporter: Address, value: i128) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Reporter, &reporter)?;
+        require_strictly_positive(value)?;
+        settle_fees(&e)?;
+        set_etf_market_value(&e, value);
+        set_nav_updated_at(&e, e.ledger().timestamp());
+        require_positive_price(&e)?;
+        record_nav(&e)?;
+
+        events::etf_market_value(
+            &e,
+            reporter,
+            EtfMarketValueEvent {
+                value,
+                updated_at: e.ledger().timestamp(),
+            },
+        );
+        Ok(())
+    }
+
+    fn oracle(e: Env) -> Result<Option<OracleConfig>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_oracle(&e))
+    }
+
+    fn set_oracle(e: Env, oracle: Address, asset: Asset) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_oracle(&e, OracleConfig { oracle, asset });
+        Ok(())
+    }
+
+    fn remove_oracle(e: Env) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_oracle_removal(&e);
+        Ok(())
+    }
+
+    fn update_etf_market_value(e: Env) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        let config = get_oracle(&e).ok_or(ExcellarError::OracleNotConfigured)?;
+        sync_etf_market_value(&e, &config)
+    }
+
+    fn oracle_max_deviation(e: Env) -> Result<u32, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_oracle_max_deviation(&e))
+    }
+
+    fn set_oracle_max_deviation(e: Env, bps: u32) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_oracle_max_deviation(&e, bps);
+        Ok(())
+    }
+
+    fn nav_heartbeat(e: Env) -> Result<u64, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_nav_heartbeat(&e))
+    }
+
+    fn set_nav_heartbeat(e: Env, seconds: u64) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_nav_heartbeat(&e, seconds);
+        Ok(())
+    }
+
+    fn nav_signers(e: Env) -> Result<Option<NavSigners>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_nav_signers(&e))
+    }
+
+    fn set_nav_signers(e: Env, signers: NavSigners) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_nav_signers(&e, signers)
+    }
+
+    fn nav_nonce(e: Env) -> Result<u64, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_nav_nonce(&e))
+    }
+
+    fn signed_nav_message(e: Env, payload: NavPayload) -> Result<Bytes, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(nav_message(&e, &payload))
+    }
+
+    fn submit_nav(
+        e: Env,
+        payload: NavPayload,
+        signatures: Vec<NavSignature>,
+    ) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+        if payload.nonce <= get_nav_nonce(&e) {
+            return Err(ExcellarError::NavNonceUsed);
+        }
+
+        // The payload may not predate the NAV it replaces or, with a
+        // heartbeat set, be stale on arrival.
+        let now = e.ledger().timestamp();
+        let heartbeat = get_nav_heartbeat(&e);
+        if payload.timestamp > now
+            || payload.timestamp < get_nav_updated_at(&e)
+            || (heartbeat > 0 && now > payload.timestamp.saturating_add(heartbeat))
+        {
+            return Err(ExcellarError::InvalidNavTimestamp);
+        }
+
+        require_strictly_positive(payload.etf_market_value)?;
+        require_positive(payload.cash_reserves)?;
+        require_positive(payload.fees)?;
+        require_signed(&e, &payload, &signatures)?;
+
+        set_etf_market_value(&e, payload.etf_market_value);
+        set_cash_reserves(&e, payload.cash_reserves);
+        set_fees(&e, payload.fees);
+        set_fees_accrued_at(&e, payload.timestamp);
+        set_nav_updated_at(&e, payload.timestamp);
+        set_nav_nonce(&e, payload.nonce);
+        require_positive_price(&e)?;
+        record_nav(&e)?;
+
+        events::signed_nav(&e, payload.nonce, payload);
+        Ok(())
+    }
+
+    fn nav_updated_at(e: Env) -> Result<u64, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_nav_updated_at(&e))
+    }
+
+    fn nav_history_len(e: Env) -> Result<u32, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_nav_history_len(&e))
+    }
+
+    fn nav_history(e: Env, start: u32, limit: u32) -> Result<Vec<NavSnapshot>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_nav_history(&e, start, limit))
+    }
+
+    fn nav_at(e: Env, timestamp: u64) -> Result<NavSnapshot, ExcellarError> {
+        require_initialized(&e)?;
+        get_nav_at(&e, timestamp).ok_or(ExcellarError::NavNotFound)
+    }
+
+    fn price_at(e: Env, timestamp: u64) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        get_nav_at(&e, timestamp)
+            .map(|snapshot| snapshot.price)
+            .ok_or(ExcellarError::NavNotFound)
+    }
+
+    fn cash_reserves(e: Env) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_cash_reserves(&e))
+    }
+
+    fn set_cash_reserves(e: Env, reporter: Address, amount: i128) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Reporter, &reporter)?;
+        require_positive(amount)?;
+        settle_fees(&e)?;
+        let previous = get_cash_reserves(&e);
+        set_cash_reserves(&e, amount);
+        require_positive_price(&e)?;
+        record_nav(&e)?;
+
+        events::cash_reserves(&e, reporter, CashReservesEvent { previous, amount });
+        Ok(())
+    }
+
+    fn fees(e: Env) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        accrued_fees(&e)
+    }
+
+    fn management_fee(e: Env) -> Result<u32, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_management_fee_bps(&e))
+    }
+
+    fn set_management_fee(e: Env, bps: u32) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_management_fee(&e, bps)
+    }
+
+    fn collect_fees(e: Env, treasurer: Address, to: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Treasurer, &treasurer)?;
+        require_no_timelock(&e)?;
+        apply_fee_collection(&e, FeeCollection { treasurer, to })
+    }
+
+    fn transaction_fees(e: Env) -> Result<Option<TransactionFees>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_transaction_fees(&e))
+    }
+
+    fn set_transaction_fees(e: Env, fees: TransactionFees) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_transaction_fees(&e, fees)
+    }
+
+    fn attest(
+        e: Env,
+        auditor: Address,
+        report_hash: BytesN<32>,
+        custody_total: i128,
+        reported_at: u64,
+        uri: String,
+    ) -> Result<ReserveRatio, ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Auditor, &auditor)?;
+        require_positive(custody_total)?;
+
+        // Reports can't be dated in the future or predate the latest one.
+        if reported_at > e.ledger().timestamp() {
+            return Err(ExcellarError::InvalidAttestation);
+        }
+        if let Some(latest) = get_latest_attestation(&e) {
+            if reported_at < latest.reported_at {
+                return Err(ExcellarError::InvalidAttestation);
+            }
+        }
+
+        let attestation = Attestation {
+            attester: auditor.clone(),
+            report_hash,
+            custody_total,
+            reported_at,
+            uri,
+            recorded_at: e.ledger().timestamp(),
+        };
+        push_attestation(&e, &attestation);
+        events::attestation(&e, auditor, attestation);
+
+        let ratio = current_reserve_ratio(&e)?;
+        if ratio.below_floor {
+            events::reserve_shortfall(&e, ratio.clone());
+        }
+        Ok(ratio)
+    }
+
+    fn attestation_count(e: Env) -> Result<u32, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_attestation_count(&e))
+    }
+
+    fn attestations(e: Env, start: u32, limit: u32) -> Result<Vec<Attestation>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_attestations(&e, start, limit))
+    }
+
+    fn latest_attestation(e: Env) -> Result<Attestation, ExcellarError> {
+        require_initialized(&e)?;
+        get_latest_attestation(&e).ok_or(ExcellarError::NoAttestation)
+    }
+
+    fn reserve_ratio(e: Env) -> Result<ReserveRatio, ExcellarError> {
+        require_initialized(&e)?;
+        current_reserve_ratio(&e)
+    }
+
+    fn reserve_ratio_floor(e: Env) -> Result<u32, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_reserve_ratio_floor(&e))
+    }
+
+    fn set_reserve_ratio_floor(e: Env, bps: u32) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        let previous_bps = get_reserve_ratio_floor(&e);
+        set_reserve_ratio_floor(&e, bps);
+
+        events::reserve_ratio_floor(&e, ReserveRatioFloorEvent { previous_bps, bps });
+        Ok(())
+    }
+
+    fn grant_role(e: Env, role: Role, account: Address) -> Result<(), GovernanceError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        grant_role(&e, role, account);
+        Ok(())
+    }
+
+    fn revoke_role(e: Env, role: Role, account: Address) -> Result<(), GovernanceError> {
+        require_admin(&e)?;
+        // Revoking only takes power away, so a compromised key can be cut
+        // off at once. Guardians are the exception: they are the check on
+        // the admin and must see their own removal coming.
+        if role == Role::Guardian {
+            require_no_timelock(&e)?;
+        }
+        revoke_role(&e, role, account);
+        Ok(())
+    }
+
+    fn has_role(e: Env, role: Role, account: Address) -> Result<bool, GovernanceError> {
+        require_initialized(&e)?;
+        Ok(has_role(&e, role, &account))
+    }
+
+    fn role_members(e: Env, role: Role) -> Result<Vec<Address>, GovernanceError> {
+        require_initialized(&e)?;
+        Ok(role_members(&e, role))
+    }
+
+    fn pause(e: Env, pauser: Address, operation: Operation) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Pauser, &pauser)?;
+        set_paused(&e, pauser, operation, true);
+        Ok(())
+    }
+
+    fn unpause(e: Env, pauser: Address, operation: Operation) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Pauser, &pauser)?;
+        set_paused(&e, pauser, operation, false);
+        Ok(())
+    }
+
+    fn is_paused(e: Env, operation: Operation) -> Result<bool, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(is_paused(&e, operation))
+    }
+
+    fn add_investors(
+        e: Env,
+        compliance: Address,
+        investors: Vec<Investor>,
+    ) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Compliance, &compliance)?;
+        for investor in investors.iter() {
+            set_investor(&e, investor.clone());
+            events::investor_added(&e, compliance.clone(), investor);
+        }
+        Ok(())
+    }
+
+    fn remove_investors(
+        e: Env,
+        compliance: Address,
+        accounts: Vec<Address>,
+    ) -> Result<(), ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Compliance, &compliance)?;
+        for account in accounts.iter() {
+            remove_investor(&e, account.clone());
+            events::investor_removed(&e, compliance.clone(), account);
+        }
+        Ok(())
+    }
+
+    fn investor(e: Env, account: Address) -> Result<Option<AllowlistEntry>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_investor(&e, account))
+    }
+
+    fn is_allowlisted(e: Env, account: Address) -> Result<bool, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(require_allowlisted(&e, &account).is_ok())
+    }
+
+    fn allowlist_enabled(e: Env) -> Result<bool, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(is_allowlist_enabled(&e))
+    }
+
+    fn set_allowlist_enabled(e: Env, enabled: bool) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        set_allowlist_enabled(&e, enabled);
+
+        events::allowlist_enabled(&e, enabled);
+        Ok(())
+    }
+
+    fn limits(e: Env) -> Result<Limits, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_limits(&e))
+    }
+
+    fn set_limits(e: Env, limits: Limits) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_limits(&e, limits);
+        Ok(())
+    }
+
+    fn liquidity_buffer(e: Env) -> Result<LiquidityBuffer, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_liquidity_buffer(&e))
+    }
+
+    fn set_liquidity_buffer(e: Env, buffer: LiquidityBuffer) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_liquidity_buffer(&e, buffer)
+    }
+
+    fn admin_withdrawable(e: Env, asset: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        if get_collateral(&e, &asset).is_none() {
+            return Err(ExcellarError::UnsupportedCollateral);
+        }
+        admin_withdrawable(&e, &asset)
+    }
+
+    fn remaining_deposit_capacity(e: Env, account: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        let xusg_price = calculate_xusg_price(&e)?;
+        Ok(remaining_deposit_capacity(
+            &e,
+            calculate_total_assets(&e)?,
+            position_value(&e, &account, xusg_price)?,
+        ))
+    }
+
+    fn remaining_redemption_capacity(e: Env, account: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(remaining_redemption_capacity(&e, account))
+    }
+
+    fn balance(e: Env, account: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        let xusg_contract = get_token_xusg(&e);
+        Ok(token::Client::new(&e, &xusg_contract).balance(&account))
+    }
+
+    fn asset(e: Env) -> Result<Address, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_token_usdc(&e))
+    }
+
+    fn total_assets(e: Env) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        calculate_total_assets(&e)
+    }
+
+    fn convert_to_shares(e: Env, assets: i128) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        mul_div(assets, price_unit(&e), calculate_xusg_price(&e)?)
+    }
+
+    fn convert_to_assets(e: Env, shares: i128) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        mul_div(shares, calculate_xusg_price(&e)?, price_unit(&e))
+    }
+
+    fn max_deposit(e: Env, receiver: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        if is_paused(&e, Operation::Deposit) || require_allowlisted(&e, &receiver).is_err() {
+            return Ok(0);
+        }
+
+        // The caps apply to the amount after fees, so this is a lower bound.
+        let xusg_price = calculate_xusg_price(&e)?;
+        Ok(remaining_deposit_capacity(
+            &e,
+            calculate_total_assets(&e)?,
+            position_value(&e, &receiver, xusg_price)?,
+        ))
+    }
+
+    fn max_withdraw(e: Env, owner: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        if is_paused(&e, Operation::Withdraw) || require_allowlisted(&e, &owner).is_err() {
+            return Ok(0);
+        }
+
+        let xusg_price = calculate_xusg_price(&e)?;
+        let value = position_value(&e, &owner, xusg_price)?
+            .min(remaining_redemption_capacity(&e, owner))
+            .min(available_balance(&e, &get_token_usdc(&e))?);
+        let xusg = mul_div(value, price_unit(&e), xusg_price)?;
+        Ok(withdraw_receipt(&e, get_token_usdc(&e), xusg, xusg_price)?
+            .net
+            .max(0))
+    }
+
+    fn preview_deposit(e: Env, assets: i128) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        let receipt = deposit_receipt(&e, get_token_usdc(&e), assets, calculate_xusg_price(&e)?)?;
+        Ok(receipt.xusg.max(0))
+    }
+
+    fn preview_mint(e: Env, shares: i128) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(mint_cost(&e, shares, calculate_xusg_price(&e)?)?.max(0))
+    }
+
+    fn preview_redeem(e: Env, assets: i128) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(redeem_cost(&e, assets, calculate_xusg_price(&e)?)?.max(0))
+    }
+
+    fn mint(e: Env, to: Address, xusg_amount: i128) -> Result<DepositReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        to.require_auth();
+        if xusg_amount <= 0 {
+            return Err(ExcellarError::DepositMustBePositive);
+        }
+
+        let gross = mint_cost(&e, xusg_amount, fresh_xusg_price(&e)?)?;
+        process_deposit(
+            &e,
+            Funding::Transfer,
+            to.clone(),
+            to,
+            get_token_usdc(&e),
+            gross,
+            xusg_amount,
+        )
+    }
+
+    fn redeem(e: Env, to: Address, usdc_amount: i128) -> Result<WithdrawReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        to.require_auth();
+        if usdc_amount <= 0 {
+            return Err(ExcellarError::WithdrawalMustBePositive);
+        }
+
+        let xusg_amount = redeem_cost(&e, usdc_amount, fresh_xusg_price(&e)?)?;
+        process_withdraw(
+            &e,
+            to.clone(),
+            to,
+            get_token_usdc(&e),
+            xusg_amount,
+            usdc_amount,
+        )
+    }
+
+    fn price(e: Env) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        calculate_xusg_price(&e)
+    }
+
+    fn total(e: Env) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_total_xusg(&e))
+    }
+
+    fn collateral_assets(e: Env) -> Result<Vec<Address>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_collateral_assets(&e))
+    }
+
+    fn collateral(e: Env, asset: Address) -> Result<Option<Collateral>, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_collateral(&e, &asset))
+    }
+
+    fn set_collateral(e: Env, asset: Address, collateral: Collateral) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_collateral(&e, CollateralChange { asset, collateral })
+    }
+
+    fn remove_collateral(e: Env, asset: Address) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_collateral_removal(&e, asset)
+    }
+
+    fn collateral_heartbeat(e: Env) -> Result<u64, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_collateral_heartbeat(&e))
+    }
+
+    fn set_collateral_heartbeat(e: Env, seconds: u64) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_collateral_heartbeat(&e, seconds);
+        Ok(())
+    }
+
+    fn asset_reserves(e: Env, asset: Address) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_asset_reserves(&e, &asset))
+    }
+
+    fn deposit(
+        e: Env,
+        to: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<DepositReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        to.require_auth();
+        process_deposit(&e, Funding::Transfer, to.clone(), to, asset, amount, 0)
+    }
+
+    fn deposit_checked(
+        e: Env,
+        to: Address,
+        asset: Address,
+        amount: i128,
+        min_xusg_out: i128,
+        deadline: u64,
+    ) -> Result<DepositReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        to.require_auth();
+        require_before_deadline(&e, deadline)?;
+        process_deposit(
+            &e,
+            Funding::Transfer,
+            to.clone(),
+            to,
+            asset,
+            amount,
+            min_xusg_out,
+        )
+    }
+
+    fn deposit_for(
+        e: Env,
+        from: Address,
+        beneficiary: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<DepositReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        from.require_auth();
+        process_deposit(&e, Funding::Transfer, from, beneficiary, asset, amount, 0)
+    }
+
+    fn deposit_from(
+        e: Env,
+        from: Address,
+        to: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<DepositReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        from.require_auth();
+        process_deposit(&e, Funding::Allowance, from, to, asset, amount, 0)
+    }
+
+    fn withdraw_to(
+        e: Env,
+        owner: Address,
+        receiver: Address,
+        asset: Address,
+        xusg_amount: i128,
+    ) -> Result<WithdrawReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        owner.require_auth();
+        process_withdraw(&e, owner, receiver, asset, xusg_amount, 0)
+    }
+
+    fn withdraw(
+        e: Env,
+        to: Address,
+        asset: Address,
+        xusg_amount: i128,
+    ) -> Result<WithdrawReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        to.require_auth();
+        process_withdraw(&e, to.clone(), to, asset, xusg_amount, 0)
+    }
+
+    fn withdraw_checked(
+        e: Env,
+        to: Address,
+        asset: Address,
+        xusg_amount: i128,
+        min_amount_out: i128,
+        deadline: u64,
+    ) -> Result<WithdrawReceipt, ExcellarError> {
+        require_initialized(&e)?;
+        to.require_auth();
+        require_before_deadline(&e, deadline)?;
+        process_withdraw(&e, to.clone(), to, asset, xusg_amount, min_amount_out)
+    }
+
+    fn withdraw_admin(
+        e: Env,
+        treasurer: Address,
+        to: Address,
+        asset: Address,
+        amount: i128,
+    ) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        require_role(&e, Role::Treasurer, &treasurer)?;
+        require_no_timelock(&e)?;
+        apply_admin_withdrawal(
+            &e,
+            AdminWithdrawal {
+                treasurer,
+                to,
+                asset,
+                amount,
+            },
+        )
+    }
+
+    fn request_redeem(
+        e: Env,
+        owner: Address,
+        asset: Address,
+        xusg_amount: i128,
+    ) -> Result<u64, ExcellarError> {
+        require_initialized(&e)?;
+        owner.require_auth();
+        require_not_paused(&e, Operation::Withdraw)?;
+        require_allowlisted(&e, &owner)?;
+        let xusg_token = token::Client::new(&e, &get_token_xusg(&e));
+
+        if xusg_amount <= 0 {
+            return Err(ExcellarError::WithdrawalMustBePositive);
+        }
+        if get_collateral(&e, &asset).is_none() {
+            return Err(ExcellarError::UnsupportedCollateral);
+        }
+
+        if xusg_amount > xusg_token.balance(&owner) {
+            return Err(ExcellarError::InsufficientBalance);
+        }
+
+        xusg_token.transfer(&owner, &e.current_contract_address(), &xusg_amount);
+
+        let locked_price = match get_redemption_price_mode(&e) {
+            RedemptionPriceMode::AtRequest => Some(fresh_xusg_price(&e)?),
+            RedemptionPriceMode::AtFulfilment => None,
+        };
+
+        let request = RedemptionRequest {
+            owner,
+            asset,
+            xusg_amount,
+            locked_price,
+            requested_at: e.ledger().timestamp(),
+        };
+        let request_id = push_redemption(&e, &request)?;
+
+        events::redeem_requested(&e, request_id, request);
+        Ok(request_id)
+    }
+
+    fn cancel_redeem(e: Env, owner: Address, request_id: u64) -> Result<i128, ExcellarError> {
+        require_initialized(&e)?;
+        owner.require_auth();
+
+        let request = get_redemption(&e, request_id).ok_or(ExcellarError::RedemptionNotFound)?;
+        if request.owner != owner {
+            return Err(ExcellarError::NotRedemptionOwner);
+        }
+
+        remove_redemption(&e, request_id, &request)?;
+        transfer(&e, get_token_xusg(&e), owner.clone(), request.xusg_amount);
+
+        events::redeem_cancelled(&e, owner, request_id, request.xusg_amount);
+        Ok(request.xusg_amount)
+    }
+
+    fn fulfil_redemptions(e: Env, max_requests: u32) -> Result<u32, ExcellarError> {
+        require_initialized(&e)?;
+        require_not_paused(&e, Operation::Withdraw)?;
+
+        let next_id = get_next_redemption_id(&e);
+        let mut head = get_redemption_head(&e);
+        let mut fulfilled = 0;
+
+        // Requests are paid strictly in order: stop at the first one that the
+        // collateral on hand cannot cover rather than skipping ahead of it.
+        for _ in 0..max_requests {
+            if head >= next_id {
+                break;
+            }
+
+            if let Some(request) = get_redemption(&e, head) {
+                let xusg_price = match request.locked_price {
+                    Some(locked_price) => locked_price,
+                    None => fresh_xusg_price(&e)?,
+                };
+                // Requests for collateral that has since been removed are
+                // paid in USDC.
+                let asset = match get_collateral(&e, &request.asset) {
+                    Some(_) => request.asset.clone(),
+                    None => get_token_usdc(&e),
+                };
+                let receipt = withdraw_receipt(&e, asset.clone(), request.xusg_amount, xusg_price)?;
+
+                if receipt.gross > get_balance(&e, asset) {
+                    break;
+                }
+                // A request over a daily redemption limit waits for the
+                // window to reset, holding back the requests behind it.
+                if record_redemption(&e, request.owner.clone(), receipt.value).is_err() {
+                    break;
+                }
+
+                remove_redemption(&e, head, &request)?;
+                settle_withdrawal(&e, request.owner.clone(), &receipt)?;
+
+                events::redeemed(&e, request.owner, head, receipt);
+                fulfilled += 1;
+            }
+
+            head += 1;
+        }
+
+        set_redemption_head(&e, head);
+        Ok(fulfilled)
+    }
+
+    fn redemption(e: Env, request_id: u64) -> Result<RedemptionRequest, ExcellarError> {
+        require_initialized(&e)?;
+        get_redemption(&e, request_id).ok_or(ExcellarError::RedemptionNotFound)
+    }
+
+    fn redemption_queue_head(e: Env) -> Result<u64, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_redemption_head(&e))
+    }
+
+    fn redemption_price_mode(e: Env) -> Result<RedemptionPriceMode, ExcellarError> {
+        require_initialized(&e)?;
+        Ok(get_redemption_price_mode(&e))
+    }
 
-        transfer_usdc(&e, to, usdc_amount);
-        Ok(usdc_amount)
+    fn set_redemption_price_mode(e: Env, mode: RedemptionPriceMode) -> Result<(), ExcellarError> {
+        require_admin(&e)?;
+        require_no_timelock(&e)?;
+        apply_redemption_price_mode(&e, mode);
+        Ok(())
     }
 }
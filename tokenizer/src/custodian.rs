@@ -0,0 +1,99 @@
+use crate::error::ExcellarError;
+use crate::storage::DataKey;
+use soroban_sdk::{contracttype, xdr::ToXdr, Bytes, BytesN, Env, Vec};
+
+/// Custodian ed25519 keys allowed to sign NAV updates, and how many of them
+/// have to sign each one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct NavSigners {
+    pub keys: Vec<BytesN<32>>,
+    pub threshold: u32,
+}
+
+/// A NAV update as signed off-chain by the custodian keys.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct NavPayload {
+    pub etf_market_value: i128,
+    pub cash_reserves: i128,
+    /// Management fees owed as of `timestamp`.
+    pub fees: i128,
+    /// Timestamp the figures are as of.
+    pub timestamp: u64,
+    /// Must exceed the nonce of the previous accepted payload.
+    pub nonce: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct NavSignature {
+    pub public_key: BytesN<32>,
+    pub signature: BytesN<64>,
+}
+
+pub fn get_nav_signers(e: &Env) -> Option<NavSigners> {
+    e.storage().instance().get(&DataKey::NavSigners)
+}
+
+pub fn set_nav_signers(e: &Env, signers: &NavSigners) {
+    e.storage().instance().set(&DataKey::NavSigners, signers)
+}
+
+/// Nonce of the latest accepted payload, zero before the first one.
+pub fn get_nav_nonce(e: &Env) -> u64 {
+    e.storage().instance().get(&DataKey::NavNonce).unwrap_or(0)
+}
+
+pub fn set_nav_nonce(e: &Env, nonce: u64) {
+    e.storage().instance().set(&DataKey::NavNonce, &nonce)
+}
+
+pub fn require_valid_signers(signers: &NavSigners) -> Result<(), ExcellarError> {
+    if signers.threshold == 0 || signers.threshold > signers.keys.len() {
+        return Err(ExcellarError::InvalidSignerConfig);
+    }
+
+    for (index, key) in signers.keys.iter().enumerate() {
+        if signers.keys.first_index_of(&key) != Some(index as u32) {
+            return Err(ExcellarError::InvalidSignerConfig);
+        }
+    }
+    Ok(())
+}
+
+/// The bytes the custodian keys sign: the payload bound to this contract, so
+/// it can't be replayed against another tokenizer.
+pub fn nav_message(e: &Env, payload: &NavPayload) -> Bytes {
+    (e.current_contract_address(), payload.clone()).to_xdr(e)
+}
+
+/// Checks that at least the threshold of distinct registered keys signed
+/// `payload`. An invalid signature traps in `ed25519_verify`.
+pub fn require_signed(
+    e: &Env,
+    payload: &NavPayload,
+    signatures: &Vec<NavSignature>,
+) -> Result<(), ExcellarError> {
+    let signers = get_nav_signers(e).ok_or(ExcellarError::InvalidSignerConfig)?;
+    let message = nav_message(e, payload);
+
+    let mut signed = Vec::<BytesN<32>>::new(e);
+    for signature in signatures.iter() {
+        if !signers.keys.contains(&signature.public_key) {
+            return Err(ExcellarError::UnknownSigner);
+        }
+        if signed.contains(&signature.public_key) {
+            return Err(ExcellarError::DuplicateSigner);
+        }
+
+        e.crypto()
+            .ed25519_verify(&signature.public_key, &message, &signature.signature);
+        signed.push_back(signature.public_key);
+    }
+
+    if signed.len() < signers.threshold {
+        return Err(ExcellarError::InsufficientSignatures);
+    }
+    Ok(())
+}
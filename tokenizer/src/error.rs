@@ -1,5 +1,10 @@
 use soroban_sdk::contracterror;
 
+/// Errors from investing in the fund and running it: trading, pricing,
+/// limits, fees, collateral and reporting.
+///
+/// `ExcellarError` and `GovernanceError` share one range of codes, so a code
+/// means the same thing whichever of them it comes from.
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -7,4 +12,97 @@ pub enum ExcellarError {
     DepositMustBePositive = 1,
     WithdrawalMustBePositive = 2,
     InsufficientBalance = 3,
+    InsufficientLiquidity = 4,
+    RedemptionNotFound = 5,
+    NotRedemptionOwner = 6,
+    StalePrice = 7,
+    OraclePriceUnavailable = 8,
+    OracleNotConfigured = 9,
+    Unauthorized = 10,
+    DepositsPaused = 11,
+    WithdrawalsPaused = 12,
+    AdminWithdrawalsPaused = 13,
+    NotAllowlisted = 14,
+    AllowlistExpired = 15,
+    TotalAssetsCapExceeded = 16,
+    PositionCapExceeded = 17,
+    AddressRedemptionLimitExceeded = 18,
+    GlobalRedemptionLimitExceeded = 19,
+    InvalidFeeRate = 20,
+    FeeExceedsAmount = 21,
+    NavNotFound = 25,
+    NotInitialized = 26,
+    AlreadyInitialized = 27,
+    InvalidPriceDecimals = 28,
+    NegativeAmount = 29,
+    AmountMustBePositive = 30,
+    PriceNotPositive = 31,
+    UnsupportedCollateral = 32,
+    InvalidCollateral = 33,
+    SlippageExceeded = 34,
+    DeadlineExpired = 35,
+    InsufficientAllowance = 36,
+    InvalidAttestation = 37,
+    NoAttestation = 38,
+    InvalidSignerConfig = 39,
+    UnknownSigner = 40,
+    DuplicateSigner = 41,
+    InsufficientSignatures = 42,
+    NavNonceUsed = 43,
+    InvalidNavTimestamp = 44,
+    TimelockRequired = 45,
+    InvalidLiquidityBuffer = 50,
+    ArithmeticOverflow = 51,
+    OracleDeviationExceeded = 52,
+    LiquidityBufferBreached = 53,
+    CollateralFunded = 54,
+    ShareDecimalsMismatch = 55,
+}
+
+/// Errors from controlling the contract: the admin handover, roles, the
+/// timelock and upgrades.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum GovernanceError {
+    Unauthorized = 10,
+    UnknownSchemaVersion = 22,
+    NoPendingAdmin = 23,
+    AdminProposalExpired = 24,
+    NotInitialized = 26,
+    TimelockRequired = 45,
+    OperationAlreadyScheduled = 46,
+    OperationNotFound = 47,
+    OperationNotReady = 48,
+    InvalidTimelockDelay = 49,
+    OperationNotCancellable = 56,
+}
+
+/// Failures of the initialization, admin, role and timelock checks that both
+/// kinds of entrypoint run. `?` turns them into the entrypoint's own error.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AccessError {
+    Unauthorized,
+    NotInitialized,
+    TimelockRequired,
+}
+
+impl From<AccessError> for ExcellarError {
+    fn from(error: AccessError) -> Self {
+        match error {
+            AccessError::Unauthorized => ExcellarError::Unauthorized,
+            AccessError::NotInitialized => ExcellarError::NotInitialized,
+            AccessError::TimelockRequired => ExcellarError::TimelockRequired,
+        }
+    }
+}
+
+impl From<AccessError> for GovernanceError {
+    fn from(error: AccessError) -> Self {
+        match error {
+            AccessError::Unauthorized => GovernanceError::Unauthorized,
+            AccessError::NotInitialized => GovernanceError::NotInitialized,
+            AccessError::TimelockRequired => GovernanceError::TimelockRequired,
+        }
+    }
 }
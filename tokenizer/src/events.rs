@@ -0,0 +1,292 @@
+use crate::admin::PendingAdmin;
+use crate::allowlist::Investor;
+use crate::attestation::{Attestation, ReserveRatio};
+use crate::collateral::Collateral;
+use crate::custodian::{NavPayload, NavSigners};
+use crate::fees::TransactionFees;
+use crate::limits::{Limits, LiquidityBuffer};
+use crate::oracle::OracleConfig;
+use crate::pause::Operation;
+use crate::receipt::{DepositReceipt, WithdrawReceipt};
+use crate::redemption::{RedemptionPriceMode, RedemptionRequest};
+use crate::roles::Role;
+use crate::timelock::{ScheduledOperation, TimelockCall};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Symbol};
+
+/// Published once by `initialize`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct InitializedEvent {
+    pub admin: Address,
+    pub token_usdc: Address,
+    pub token_xusg: Address,
+    pub price_decimals: u32,
+}
+
+/// A new ETF market value, as of `updated_at`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct EtfMarketValueEvent {
+    pub value: i128,
+    pub updated_at: u64,
+}
+
+/// A reported change of the cash reserves from `previous` to `amount`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CashReservesEvent {
+    pub previous: i128,
+    pub amount: i128,
+}
+
+/// Collateral moved out of the contract by a treasurer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AdminWithdrawEvent {
+    pub to: Address,
+    pub asset: Address,
+    pub amount: i128,
+}
+
+/// A change of the annual management fee rate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ManagementFeeEvent {
+    pub previous_bps: u32,
+    pub bps: u32,
+}
+
+/// Accrued management fees paid out by a treasurer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeesCollectedEvent {
+    pub to: Address,
+    pub amount: i128,
+}
+
+/// A change of the reserve ratio below which a shortfall is flagged.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ReserveRatioFloorEvent {
+    pub previous_bps: u32,
+    pub bps: u32,
+}
+
+pub fn initialized(e: &Env, event: InitializedEvent) {
+    e.events().publish((symbol_short!("init"),), event);
+}
+
+pub fn upgraded(e: &Env, new_wasm_hash: BytesN<32>) {
+    e.events()
+        .publish((symbol_short!("upgraded"),), new_wasm_hash);
+}
+
+pub fn migrated(e: &Env, from: u32, to: u32) {
+    e.events().publish((symbol_short!("migrated"),), (from, to));
+}
+
+pub fn admin_proposed(e: &Env, admin: Address, pending: PendingAdmin) {
+    e.events()
+        .publish((Symbol::new(e, "admin_proposed"), admin), pending);
+}
+
+pub fn admin_changed(e: &Env, previous: Address, admin: Address) {
+    e.events()
+        .publish((Symbol::new(e, "admin_changed"), previous), admin);
+}
+
+pub fn deposit(e: &Env, to: Address, receipt: DepositReceipt) {
+    e.events().publish((symbol_short!("deposit"), to), receipt);
+}
+
+pub fn withdraw(e: &Env, to: Address, receipt: WithdrawReceipt) {
+    e.events().publish((symbol_short!("withdraw"), to), receipt);
+}
+
+pub fn redeem_requested(e: &Env, request_id: u64, request: RedemptionRequest) {
+    e.events().publish(
+        (
+            Symbol::new(e, "redeem_requested"),
+            request.owner.clone(),
+            request_id,
+        ),
+        request,
+    );
+}
+
+/// `xusg_amount` is the escrowed XUSG returned to the owner.
+pub fn redeem_cancelled(e: &Env, owner: Address, request_id: u64, xusg_amount: i128) {
+    e.events().publish(
+        (Symbol::new(e, "redeem_cancelled"), owner, request_id),
+        xusg_amount,
+    );
+}
+
+pub fn redeemed(e: &Env, owner: Address, request_id: u64, receipt: WithdrawReceipt) {
+    e.events()
+        .publish((Symbol::new(e, "redeemed"), owner, request_id), receipt);
+}
+
+pub fn admin_withdraw(e: &Env, treasurer: Address, event: AdminWithdrawEvent) {
+    e.events()
+        .publish((Symbol::new(e, "admin_withdraw"), treasurer), event);
+}
+
+/// `source` is the reporter that set the value or the oracle it was read from.
+pub fn etf_market_value(e: &Env, source: Address, event: EtfMarketValueEvent) {
+    e.events()
+        .publish((Symbol::new(e, "etf_market_value"), source), event);
+}
+
+pub fn oracle_set(e: &Env, config: OracleConfig) {
+    e.events().publish((Symbol::new(e, "oracle_set"),), config);
+}
+
+pub fn oracle_removed(e: &Env) {
+    e.events().publish((Symbol::new(e, "oracle_removed"),), ());
+}
+
+pub fn nav_heartbeat(e: &Env, seconds: u64) {
+    e.events()
+        .publish((Symbol::new(e, "nav_heartbeat"),), seconds);
+}
+
+pub fn cash_reserves(e: &Env, reporter: Address, event: CashReservesEvent) {
+    e.events()
+        .publish((Symbol::new(e, "cash_reserves"), reporter), event);
+}
+
+pub fn management_fee(e: &Env, event: ManagementFeeEvent) {
+    e.events()
+        .publish((Symbol::new(e, "management_fee"),), event);
+}
+
+pub fn collateral_set(e: &Env, asset: Address, collateral: Collateral) {
+    e.events()
+        .publish((Symbol::new(e, "collateral_set"), asset), collateral);
+}
+
+pub fn collateral_revalued(e: &Env, asset: Address, event: CashReservesEvent) {
+    e.events()
+        .publish((Symbol::new(e, "collateral_revalued"), asset), event);
+}
+
+pub fn collateral_heartbeat(e: &Env, seconds: u64) {
+    e.events()
+        .publish((Symbol::new(e, "collateral_heartbeat"),), seconds);
+}
+
+pub fn collateral_removed(e: &Env, asset: Address) {
+    e.events()
+        .publish((Symbol::new(e, "collateral_removed"), asset), ());
+}
+
+pub fn transaction_fees(e: &Env, fees: TransactionFees) {
+    e.events()
+        .publish((Symbol::new(e, "transaction_fees"),), fees);
+}
+
+pub fn fees_collected(e: &Env, treasurer: Address, event: FeesCollectedEvent) {
+    e.events()
+        .publish((Symbol::new(e, "fees_collected"), treasurer), event);
+}
+
+pub fn attestation(e: &Env, attester: Address, attestation: Attestation) {
+    e.events()
+        .publish((Symbol::new(e, "attestation"), attester), attestation);
+}
+
+/// The latest attestation covers less than the floor of the XUSG liabilities.
+pub fn reserve_shortfall(e: &Env, ratio: ReserveRatio) {
+    e.events()
+        .publish((Symbol::new(e, "reserve_shortfall"),), ratio);
+}
+
+pub fn reserve_ratio_floor(e: &Env, event: ReserveRatioFloorEvent) {
+    e.events()
+        .publish((Symbol::new(e, "reserve_ratio_floor"),), event);
+}
+
+pub fn nav_signers(e: &Env, signers: NavSigners) {
+    e.events()
+        .publish((Symbol::new(e, "nav_signers"),), signers);
+}
+
+pub fn signed_nav(e: &Env, nonce: u64, payload: NavPayload) {
+    e.events()
+        .publish((Symbol::new(e, "signed_nav"), nonce), payload);
+}
+
+pub fn operation_scheduled(e: &Env, hash: BytesN<32>, operation: ScheduledOperation) {
+    e.events()
+        .publish((Symbol::new(e, "op_scheduled"), hash), operation);
+}
+
+pub fn operation_executed(e: &Env, hash: BytesN<32>, call: TimelockCall) {
+    e.events()
+        .publish((Symbol::new(e, "op_executed"), hash), call);
+}
+
+pub fn operation_cancelled(e: &Env, guardian: Address, hash: BytesN<32>) {
+    e.events()
+        .publish((Symbol::new(e, "op_cancelled"), guardian), hash);
+}
+
+pub fn timelock_delay(e: &Env, seconds: u64) {
+    e.events()
+        .publish((Symbol::new(e, "timelock_delay"),), seconds);
+}
+
+pub fn oracle_max_deviation(e: &Env, bps: u32) {
+    e.events()
+        .publish((Symbol::new(e, "oracle_max_deviation"),), bps);
+}
+
+pub fn liquidity_buffer(e: &Env, buffer: LiquidityBuffer) {
+    e.events()
+        .publish((Symbol::new(e, "liquidity_buffer"),), buffer);
+}
+
+pub fn limits(e: &Env, limits: Limits) {
+    e.events().publish((symbol_short!("limits"),), limits);
+}
+
+pub fn redemption_price_mode(e: &Env, mode: RedemptionPriceMode) {
+    e.events()
+        .publish((Symbol::new(e, "redemption_price_mode"),), mode);
+}
+
+pub fn investor_added(e: &Env, compliance: Address, investor: Investor) {
+    e.events()
+        .publish((Symbol::new(e, "investor_added"), compliance), investor);
+}
+
+pub fn investor_removed(e: &Env, compliance: Address, account: Address) {
+    e.events()
+        .publish((Symbol::new(e, "investor_removed"), compliance), account);
+}
+
+pub fn allowlist_enabled(e: &Env, enabled: bool) {
+    e.events()
+        .publish((Symbol::new(e, "allowlist_enabled"),), enabled);
+}
+
+pub fn role_granted(e: &Env, role: Role, account: Address) {
+    e.events()
+        .publish((Symbol::new(e, "role_granted"), role, account), ());
+}
+
+pub fn role_revoked(e: &Env, role: Role, account: Address) {
+    e.events()
+        .publish((Symbol::new(e, "role_revoked"), role, account), ());
+}
+
+pub fn paused(e: &Env, pauser: Address, operation: Operation) {
+    e.events()
+        .publish((symbol_short!("paused"), operation), pauser);
+}
+
+pub fn unpaused(e: &Env, pauser: Address, operation: Operation) {
+    e.events()
+        .publish((symbol_short!("unpaused"), operation), pauser);
+}
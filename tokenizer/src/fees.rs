@@ -0,0 +1,114 @@
+use crate::error::ExcellarError;
+use crate::storage::{get_cash_reserves, get_etf_market_value, get_fees, set_fees, DataKey};
+use crate::utils::{checked_add, checked_sub, mul_div, mul_div_ceil};
+use soroban_sdk::{contracttype, Address, Env};
+
+pub(crate) const BPS_DENOMINATOR: i128 = 10_000;
+pub(crate) const SECONDS_PER_YEAR: i128 = 365 * 86_400;
+
+/// Annual management fee, in basis points of the net asset value.
+pub fn get_management_fee_bps(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::ManagementFeeBps)
+        .unwrap_or(0)
+}
+
+pub fn set_management_fee_bps(e: &Env, bps: u32) {
+    e.storage().instance().set(&DataKey::ManagementFeeBps, &bps)
+}
+
+pub fn get_fees_accrued_at(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::FeesAccruedAt)
+        .unwrap_or(e.ledger().timestamp())
+}
+
+pub fn set_fees_accrued_at(e: &Env, timestamp: u64) {
+    e.storage()
+        .instance()
+        .set(&DataKey::FeesAccruedAt, &timestamp)
+}
+
+/// Management fee earned since the last settlement and not yet booked.
+pub fn pending_fees(e: &Env) -> Result<i128, ExcellarError> {
+    let elapsed = e
+        .ledger()
+        .timestamp()
+        .saturating_sub(get_fees_accrued_at(e));
+    let net_assets = checked_sub(
+        checked_add(get_etf_market_value(e), get_cash_reserves(e))?,
+        get_fees(e),
+    )?;
+    if elapsed == 0 || net_assets <= 0 {
+        return Ok(0);
+    }
+
+    mul_div(
+        net_assets,
+        i128::from(get_management_fee_bps(e)) * i128::from(elapsed),
+        BPS_DENOMINATOR * SECONDS_PER_YEAR,
+    )
+}
+
+/// Booked and pending fees together: the fund's current fee liability.
+pub fn accrued_fees(e: &Env) -> Result<i128, ExcellarError> {
+    checked_add(get_fees(e), pending_fees(e)?)
+}
+
+/// Books the pending management fee into the fee liability.
+pub fn settle_fees(e: &Env) -> Result<i128, ExcellarError> {
+    let fees = accrued_fees(e)?;
+    set_fees(e, fees);
+    set_fees_accrued_at(e, e.ledger().timestamp());
+    Ok(fees)
+}
+
+/// Entry and exit fees charged on each deposit and withdrawal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TransactionFees {
+    pub subscription_bps: u32,
+    pub redemption_bps: u32,
+    /// Lower bound on each non-zero-rate fee, in USDC.
+    pub min_fee: i128,
+    pub recipient: Address,
+}
+
+pub fn get_transaction_fees(e: &Env) -> Option<TransactionFees> {
+    e.storage().instance().get(&DataKey::TransactionFees)
+}
+
+pub fn set_transaction_fees(e: &Env, fees: &TransactionFees) {
+    e.storage().instance().set(&DataKey::TransactionFees, fees)
+}
+
+/// Fee on `gross` at `bps`, rounded up and never more than `gross` itself.
+pub fn calculate_fee(gross: i128, bps: u32, min_fee: i128) -> Result<i128, ExcellarError> {
+    if bps == 0 {
+        return Ok(0);
+    }
+
+    let fee = mul_div_ceil(gross, i128::from(bps), BPS_DENOMINATOR)?;
+    Ok(fee.max(min_fee).min(gross))
+}
+
+/// Smallest gross amount that leaves at least `net` after `calculate_fee`.
+/// Fails with `FeeExceedsAmount` if a fee of 100% makes that impossible.
+pub fn gross_for_net(net: i128, bps: u32, min_fee: i128) -> Result<i128, ExcellarError> {
+    if bps == 0 {
+        return Ok(net);
+    }
+
+    let keep = BPS_DENOMINATOR - i128::from(bps);
+    if keep <= 0 {
+        return Err(ExcellarError::FeeExceedsAmount);
+    }
+    let mut gross = mul_div_ceil(net, BPS_DENOMINATOR, keep)?.max(checked_add(net, min_fee)?);
+    // The fee itself rounds up, which can take one more unit.
+    while gross - calculate_fee(gross, bps, min_fee)? < net {
+        gross = checked_add(gross, 1)?;
+    }
+    Ok(gross)
+}
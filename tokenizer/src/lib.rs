@@ -1,10 +1,27 @@
 #![no_std]
 
+mod admin;
+mod allowlist;
+mod attestation;
+mod collateral;
 mod contract;
+mod custodian;
 mod error;
+mod events;
+mod fees;
+mod limits;
+mod nav;
+mod oracle;
+mod pause;
+mod receipt;
+mod redemption;
+mod roles;
 mod storage;
 mod test;
+mod timelock;
 mod token;
+mod upgrade;
 mod utils;
 
 pub use crate::contract::ExcellarTokenizerClient;
+pub use crate::token::ShareToken;
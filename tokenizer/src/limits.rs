@@ -0,0 +1,197 @@
+use crate::error::ExcellarError;
+use crate::fees::BPS_DENOMINATOR;
+use crate::storage::{get_record, set_record, DataKey, RecordKey};
+use crate::utils::{checked_add, mul_div_ceil};
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+/// Redemptions are counted per hour, numbered from the Unix epoch.
+pub(crate) const HOUR_IN_SECONDS: u64 = 3_600;
+
+/// Hours a daily limit adds up: the current one and the 24 before it. Every
+/// redemption counts for at least a full day, so no 24 hours can pay out
+/// more than the limit.
+pub(crate) const REDEMPTION_WINDOW_HOURS: u64 = 25;
+
+/// Subscription and redemption limits, all in USDC. `None` means unlimited.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Limits {
+    /// Maximum net asset value of the fund after a deposit.
+    pub max_total_assets: Option<i128>,
+    /// Maximum value of a single address's XUSG position after a deposit.
+    pub max_position: Option<i128>,
+    /// Maximum USDC a single address can withdraw in any 24 hours.
+    pub address_daily_redemption: Option<i128>,
+    /// Maximum USDC all addresses together can withdraw in any 24 hours.
+    pub global_daily_redemption: Option<i128>,
+}
+
+/// USDC that has to stay in the contract when a treasurer moves funds
+/// off-chain, so investors can keep withdrawing.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum LiquidityBuffer {
+    /// A share of the net asset value, in basis points.
+    Bps(u32),
+    /// A fixed amount of USDC.
+    Amount(i128),
+}
+
+/// USDC withdrawn during one hour.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RedemptionBucket {
+    pub hour: u64,
+    pub amount: i128,
+}
+
+pub fn get_liquidity_buffer(e: &Env) -> LiquidityBuffer {
+    e.storage()
+        .instance()
+        .get(&DataKey::LiquidityBuffer)
+        .unwrap_or(LiquidityBuffer::Amount(0))
+}
+
+pub fn set_liquidity_buffer(e: &Env, buffer: &LiquidityBuffer) -> Result<(), ExcellarError> {
+    match buffer {
+        LiquidityBuffer::Bps(bps) if i128::from(*bps) > BPS_DENOMINATOR => {
+            return Err(ExcellarError::InvalidLiquidityBuffer)
+        }
+        LiquidityBuffer::Amount(amount) if *amount < 0 => {
+            return Err(ExcellarError::InvalidLiquidityBuffer)
+        }
+        _ => {}
+    }
+
+    e.storage()
+        .instance()
+        .set(&DataKey::LiquidityBuffer, buffer);
+    Ok(())
+}
+
+/// USDC the buffer requires on-chain for a fund worth `total_assets`.
+pub fn required_liquidity(e: &Env, total_assets: i128) -> Result<i128, ExcellarError> {
+    Ok(match get_liquidity_buffer(e) {
+        LiquidityBuffer::Bps(bps) => {
+            mul_div_ceil(total_assets.max(0), i128::from(bps), BPS_DENOMINATOR)?
+        }
+        LiquidityBuffer::Amount(amount) => amount,
+    })
+}
+
+pub fn get_limits(e: &Env) -> Limits {
+    e.storage()
+        .instance()
+        .get(&DataKey::Limits)
+        .unwrap_or(Limits {
+            max_total_assets: None,
+            max_position: None,
+            address_daily_redemption: None,
+            global_daily_redemption: None,
+        })
+}
+
+pub fn set_limits(e: &Env, limits: &Limits) {
+    e.storage().instance().set(&DataKey::Limits, limits)
+}
+
+fn remaining(limit: Option<i128>, used: i128) -> i128 {
+    match limit {
+        Some(limit) => limit.saturating_sub(used).max(0),
+        None => i128::MAX,
+    }
+}
+
+pub fn remaining_deposit_capacity(e: &Env, total_assets: i128, position: i128) -> i128 {
+    let limits = get_limits(e);
+    remaining(limits.max_total_assets, total_assets).min(remaining(limits.max_position, position))
+}
+
+pub fn require_deposit_within_limits(
+    e: &Env,
+    total_assets: i128,
+    position: i128,
+    amount: i128,
+) -> Result<(), ExcellarError> {
+    let limits = get_limits(e);
+    if amount > remaining(limits.max_total_assets, total_assets) {
+        return Err(ExcellarError::TotalAssetsCapExceeded);
+    }
+    if amount > remaining(limits.max_position, position) {
+        return Err(ExcellarError::PositionCapExceeded);
+    }
+    Ok(())
+}
+
+/// The buckets at `key` that still count towards the daily limits, oldest
+/// first.
+fn redemption_buckets(e: &Env, key: &RecordKey) -> Vec<RedemptionBucket> {
+    let hour = e.ledger().timestamp() / HOUR_IN_SECONDS;
+    let mut buckets = Vec::new(e);
+    for bucket in get_record::<Vec<RedemptionBucket>>(e, key)
+        .unwrap_or(Vec::new(e))
+        .iter()
+    {
+        if bucket.hour + REDEMPTION_WINDOW_HOURS > hour {
+            buckets.push_back(bucket);
+        }
+    }
+    buckets
+}
+
+fn redeemed(buckets: &Vec<RedemptionBucket>) -> i128 {
+    buckets
+        .iter()
+        .fold(0, |total: i128, bucket| total.saturating_add(bucket.amount))
+}
+
+/// Adds `amount` to the bucket of the current hour.
+fn add_to_buckets(
+    e: &Env,
+    buckets: &mut Vec<RedemptionBucket>,
+    amount: i128,
+) -> Result<(), ExcellarError> {
+    let hour = e.ledger().timestamp() / HOUR_IN_SECONDS;
+    match buckets.last() {
+        Some(mut bucket) if bucket.hour == hour => {
+            bucket.amount = checked_add(bucket.amount, amount)?;
+            buckets.set(buckets.len() - 1, bucket);
+        }
+        _ => buckets.push_back(RedemptionBucket { hour, amount }),
+    }
+    Ok(())
+}
+
+pub fn remaining_redemption_capacity(e: &Env, account: Address) -> i128 {
+    let limits = get_limits(e);
+    let address_buckets = redemption_buckets(e, &RecordKey::RedemptionBuckets(account));
+    let global_buckets = redemption_buckets(e, &RecordKey::GlobalRedemptionBuckets);
+
+    remaining(limits.address_daily_redemption, redeemed(&address_buckets)).min(remaining(
+        limits.global_daily_redemption,
+        redeemed(&global_buckets),
+    ))
+}
+
+/// Counts `amount` against the daily redemption limits, failing if either
+/// the address or the global limit would be exceeded over the last day.
+pub fn record_redemption(e: &Env, account: Address, amount: i128) -> Result<(), ExcellarError> {
+    let limits = get_limits(e);
+    let address_key = RecordKey::RedemptionBuckets(account);
+    let global_key = RecordKey::GlobalRedemptionBuckets;
+    let mut address_buckets = redemption_buckets(e, &address_key);
+    let mut global_buckets = redemption_buckets(e, &global_key);
+
+    if amount > remaining(limits.address_daily_redemption, redeemed(&address_buckets)) {
+        return Err(ExcellarError::AddressRedemptionLimitExceeded);
+    }
+    if amount > remaining(limits.global_daily_redemption, redeemed(&global_buckets)) {
+        return Err(ExcellarError::GlobalRedemptionLimitExceeded);
+    }
+
+    add_to_buckets(e, &mut address_buckets, amount)?;
+    add_to_buckets(e, &mut global_buckets, amount)?;
+    set_record(e, &address_key, &address_buckets);
+    set_record(e, &global_key, &global_buckets);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::storage::{get_record, set_record, DataKey, RecordKey};
+use soroban_sdk::{contracttype, Env, Vec};
+
+/// The fund's NAV right after an update of the ETF market value or the cash
+/// reserves.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct NavSnapshot {
+    pub ledger: u32,
+    pub timestamp: u64,
+    pub etf_market_value: i128,
+    pub cash_reserves: i128,
+    /// Management fees accrued and not yet collected.
+    pub fees: i128,
+    pub total_xusg: i128,
+    pub price: i128,
+}
+
+pub fn get_nav_history_len(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::NavHistoryLen)
+        .unwrap_or(0)
+}
+
+pub fn push_nav_snapshot(e: &Env, snapshot: &NavSnapshot) {
+    let index = get_nav_history_len(e);
+    set_record(e, &RecordKey::NavSnapshot(index), snapshot);
+    e.storage()
+        .instance()
+        .set(&DataKey::NavHistoryLen, &(index + 1));
+}
+
+pub fn get_nav_snapshot(e: &Env, index: u32) -> Option<NavSnapshot> {
+    get_record(e, &RecordKey::NavSnapshot(index))
+}
+
+/// Up to `limit` snapshots, oldest first, starting at `start`.
+pub fn get_nav_history(e: &Env, start: u32, limit: u32) -> Vec<NavSnapshot> {
+    let end = start.saturating_add(limit).min(get_nav_history_len(e));
+    let mut history = Vec::new(e);
+    for index in start..end {
+        history.push_back(get_nav_snapshot(e, index).unwrap());
+    }
+    history
+}
+
+/// The latest snapshot taken at or before `timestamp`.
+pub fn get_nav_at(e: &Env, timestamp: u64) -> Option<NavSnapshot> {
+    // Snapshots are appended in ledger order, so their timestamps never
+    // decrease and the history can be bisected.
+    let mut low = 0;
+    let mut high = get_nav_history_len(e);
+    let mut found = None;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let snapshot = get_nav_snapshot(e, mid).unwrap();
+        if snapshot.timestamp <= timestamp {
+            found = Some(snapshot);
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    found
+}
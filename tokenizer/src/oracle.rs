@@ -0,0 +1,86 @@
+use crate::storage::DataKey;
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol};
+
+/// Asset identifier used by SEP-40 price feeds.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// The subset of the SEP-40 oracle interface the tokenizer reads.
+#[allow(dead_code)]
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn decimals(env: Env) -> u32;
+
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+}
+
+/// Price feed that reports the market value of the ETF position.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct OracleConfig {
+    pub oracle: Address,
+    pub asset: Asset,
+}
+
+pub fn get_oracle(e: &Env) -> Option<OracleConfig> {
+    e.storage().instance().get(&DataKey::Oracle)
+}
+
+pub fn set_oracle(e: &Env, config: &OracleConfig) {
+    e.storage().instance().set(&DataKey::Oracle, config)
+}
+
+pub fn remove_oracle(e: &Env) {
+    e.storage().instance().remove(&DataKey::Oracle)
+}
+
+/// Largest move from the stored ETF market value an oracle reading may
+/// make, in basis points. Zero disables the check.
+pub fn get_oracle_max_deviation(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::OracleMaxDeviation)
+        .unwrap_or(0)
+}
+
+pub fn set_oracle_max_deviation(e: &Env, bps: u32) {
+    e.storage()
+        .instance()
+        .set(&DataKey::OracleMaxDeviation, &bps)
+}
+
+/// Maximum age of the ETF market value, in seconds. Zero disables the check.
+pub fn get_nav_heartbeat(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::NavHeartbeat)
+        .unwrap_or(0)
+}
+
+pub fn set_nav_heartbeat(e: &Env, seconds: u64) {
+    e.storage().instance().set(&DataKey::NavHeartbeat, &seconds)
+}
+
+pub fn get_nav_updated_at(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::NavUpdatedAt)
+        .unwrap_or(0)
+}
+
+pub fn set_nav_updated_at(e: &Env, timestamp: u64) {
+    e.storage()
+        .instance()
+        .set(&DataKey::NavUpdatedAt, &timestamp)
+}
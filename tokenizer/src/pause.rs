@@ -0,0 +1,44 @@
+use crate::error::ExcellarError;
+use crate::events;
+use crate::storage::RecordKey;
+use soroban_sdk::{contracttype, Address, Env};
+
+/// Operations that can be halted independently of each other.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Operation {
+    Deposit = 0,
+    Withdraw = 1,
+    AdminWithdraw = 2,
+}
+
+pub fn is_paused(e: &Env, operation: Operation) -> bool {
+    e.storage()
+        .instance()
+        .get(&RecordKey::Paused(operation))
+        .unwrap_or(false)
+}
+
+pub fn set_paused(e: &Env, pauser: Address, operation: Operation, paused: bool) {
+    e.storage()
+        .instance()
+        .set(&RecordKey::Paused(operation), &paused);
+
+    if paused {
+        events::paused(e, pauser, operation);
+    } else {
+        events::unpaused(e, pauser, operation);
+    }
+}
+
+pub fn require_not_paused(e: &Env, operation: Operation) -> Result<(), ExcellarError> {
+    if !is_paused(e, operation) {
+        return Ok(());
+    }
+
+    Err(match operation {
+        Operation::Deposit => ExcellarError::DepositsPaused,
+        Operation::Withdraw => ExcellarError::WithdrawalsPaused,
+        Operation::AdminWithdraw => ExcellarError::AdminWithdrawalsPaused,
+    })
+}
@@ -0,0 +1,31 @@
+use soroban_sdk::{contracttype, Address};
+
+/// Breakdown of a deposit: `gross` units of `asset` paid in, of which `fee`
+/// went to the fee recipient and `net`, worth `value` USDC, bought `xusg` at
+/// `price`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositReceipt {
+    pub asset: Address,
+    pub gross: i128,
+    pub fee: i128,
+    pub net: i128,
+    pub value: i128,
+    pub xusg: i128,
+    pub price: i128,
+}
+
+/// Breakdown of a withdrawal: `xusg` redeemed at `price` for `value` USDC,
+/// paid as `gross` units of `asset` of which `fee` went to the fee recipient
+/// and `net` to the investor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct WithdrawReceipt {
+    pub asset: Address,
+    pub xusg: i128,
+    pub price: i128,
+    pub value: i128,
+    pub gross: i128,
+    pub fee: i128,
+    pub net: i128,
+}
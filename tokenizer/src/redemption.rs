@@ -0,0 +1,124 @@
+use crate::error::ExcellarError;
+use crate::storage::{get_record, price_unit, remove_record, set_record, DataKey, RecordKey};
+use crate::utils::{checked_add, checked_sub, mul_div};
+use soroban_sdk::{contracttype, Address, Env};
+
+/// When the price of a queued redemption is fixed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RedemptionPriceMode {
+    AtRequest = 0,
+    AtFulfilment = 1,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RedemptionRequest {
+    pub owner: Address,
+    /// Collateral the request is paid out in.
+    pub asset: Address,
+    pub xusg_amount: i128,
+    pub locked_price: Option<i128>,
+    pub requested_at: u64,
+}
+
+/// What the queued requests paid out in one asset add up to, so that the
+/// contract can hold their payout back from other withdrawals.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct QueuedRedemptions {
+    /// XUSG escrowed by requests priced at fulfilment.
+    pub xusg: i128,
+    /// USDC value of the requests priced when they were made.
+    pub value: i128,
+}
+
+pub fn get_redemption_price_mode(e: &Env) -> RedemptionPriceMode {
+    e.storage()
+        .instance()
+        .get(&DataKey::RedemptionPriceMode)
+        .unwrap()
+}
+
+pub fn set_redemption_price_mode(e: &Env, mode: RedemptionPriceMode) {
+    e.storage()
+        .instance()
+        .set(&DataKey::RedemptionPriceMode, &mode)
+}
+
+/// Id of the oldest request that has not been fulfilled yet.
+pub fn get_redemption_head(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::RedemptionHead)
+        .unwrap_or(0)
+}
+
+pub fn set_redemption_head(e: &Env, id: u64) {
+    e.storage().instance().set(&DataKey::RedemptionHead, &id)
+}
+
+pub fn get_next_redemption_id(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::RedemptionNextId)
+        .unwrap_or(0)
+}
+
+pub fn push_redemption(e: &Env, request: &RedemptionRequest) -> Result<u64, ExcellarError> {
+    let id = get_next_redemption_id(e);
+    set_record(e, &RecordKey::Redemption(id), request);
+    e.storage()
+        .instance()
+        .set(&DataKey::RedemptionNextId, &(id + 1));
+
+    let mut queued = get_queued_redemptions(e, &request.asset);
+    match request.locked_price {
+        Some(price) => {
+            queued.value = checked_add(queued.value, locked_value(e, request.xusg_amount, price)?)?
+        }
+        None => queued.xusg = checked_add(queued.xusg, request.xusg_amount)?,
+    }
+    set_queued_redemptions(e, &request.asset, &queued);
+    Ok(id)
+}
+
+pub fn get_redemption(e: &Env, id: u64) -> Option<RedemptionRequest> {
+    get_record(e, &RecordKey::Redemption(id))
+}
+
+/// Takes request `id`, as read from storage, out of the queue.
+pub fn remove_redemption(
+    e: &Env,
+    id: u64,
+    request: &RedemptionRequest,
+) -> Result<(), ExcellarError> {
+    remove_record(e, &RecordKey::Redemption(id));
+
+    let mut queued = get_queued_redemptions(e, &request.asset);
+    match request.locked_price {
+        Some(price) => {
+            queued.value = checked_sub(queued.value, locked_value(e, request.xusg_amount, price)?)?
+        }
+        None => queued.xusg = checked_sub(queued.xusg, request.xusg_amount)?,
+    }
+    set_queued_redemptions(e, &request.asset, &queued);
+    Ok(())
+}
+
+fn locked_value(e: &Env, xusg_amount: i128, price: i128) -> Result<i128, ExcellarError> {
+    mul_div(xusg_amount, price, price_unit(e))
+}
+
+pub fn get_queued_redemptions(e: &Env, asset: &Address) -> QueuedRedemptions {
+    e.storage()
+        .instance()
+        .get(&RecordKey::QueuedRedemptions(asset.clone()))
+        .unwrap_or_default()
+}
+
+fn set_queued_redemptions(e: &Env, asset: &Address, queued: &QueuedRedemptions) {
+    e.storage()
+        .instance()
+        .set(&RecordKey::QueuedRedemptions(asset.clone()), queued)
+}
@@ -0,0 +1,87 @@
+use crate::error::AccessError;
+use crate::events;
+use crate::storage::RecordKey;
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+/// Operational roles granted by the super-admin (`DataKey::Admin`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Role {
+    /// May update the ETF market value, cash reserves and fees.
+    Reporter = 0,
+    /// May move USDC out of the contract with `withdraw_admin`.
+    Treasurer = 1,
+    /// May halt deposits and withdrawals.
+    Pauser = 2,
+    /// May onboard and offboard investors on the allowlist.
+    Compliance = 3,
+    /// May post proof-of-reserves attestations.
+    Auditor = 4,
+    /// May cancel operations scheduled in the timelock.
+    Guardian = 5,
+}
+
+pub const ROLES: [Role; 6] = [
+    Role::Reporter,
+    Role::Treasurer,
+    Role::Pauser,
+    Role::Compliance,
+    Role::Auditor,
+    Role::Guardian,
+];
+
+pub fn role_members(e: &Env, role: Role) -> Vec<Address> {
+    e.storage()
+        .instance()
+        .get(&RecordKey::RoleMembers(role))
+        .unwrap_or(Vec::new(e))
+}
+
+pub fn has_role(e: &Env, role: Role, account: &Address) -> bool {
+    role_members(e, role).contains(account)
+}
+
+pub fn grant_role(e: &Env, role: Role, account: Address) {
+    let mut members = role_members(e, role);
+    if members.contains(&account) {
+        return;
+    }
+
+    members.push_back(account.clone());
+    e.storage()
+        .instance()
+        .set(&RecordKey::RoleMembers(role), &members);
+    events::role_granted(e, role, account);
+}
+
+pub fn revoke_role(e: &Env, role: Role, account: Address) {
+    let mut members = role_members(e, role);
+    let Some(index) = members.first_index_of(&account) else {
+        return;
+    };
+
+    members.remove(index);
+    e.storage()
+        .instance()
+        .set(&RecordKey::RoleMembers(role), &members);
+    events::role_revoked(e, role, account);
+}
+
+/// Moves every role `from` holds over to `to`.
+pub fn transfer_roles(e: &Env, from: &Address, to: &Address) {
+    for role in ROLES {
+        if has_role(e, role, from) {
+            revoke_role(e, role, from.clone());
+            grant_role(e, role, to.clone());
+        }
+    }
+}
+
+/// Checks that `caller` holds `role` and has authorized the invocation.
+pub fn require_role(e: &Env, role: Role, caller: &Address) -> Result<(), AccessError> {
+    caller.require_auth();
+    if !has_role(e, role, caller) {
+        return Err(AccessError::Unauthorized);
+    }
+    Ok(())
+}
@@ -1,5 +1,17 @@
-use crate::utils::require_positive;
-use soroban_sdk::{token, Address, ConversionError, Env, TryFromVal, Val};
+use crate::error::{AccessError, ExcellarError};
+use crate::pause::Operation;
+use crate::roles::Role;
+use crate::utils::{checked_add, checked_sub, require_positive};
+use soroban_sdk::{
+    contracttype, token, Address, BytesN, ConversionError, Env, IntoVal, TryFromVal, Val,
+};
+
+pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const RECORD_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const RECORD_LIFETIME_THRESHOLD: u32 = RECORD_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
 #[derive(Clone, Copy)]
 #[repr(u32)]
@@ -11,6 +23,31 @@ pub enum DataKey {
     ReservesCash = 6,
     Admin = 7,
     Fees = 8,
+    PriceDecimals = 9,
+    RedemptionPriceMode = 10,
+    RedemptionHead = 11,
+    RedemptionNextId = 12,
+    Oracle = 13,
+    NavHeartbeat = 14,
+    NavUpdatedAt = 15,
+    AllowlistEnabled = 16,
+    Limits = 17,
+    ManagementFeeBps = 18,
+    FeesAccruedAt = 19,
+    TransactionFees = 20,
+    SchemaVersion = 21,
+    PendingAdmin = 22,
+    NavHistoryLen = 23,
+    CollateralAssets = 24,
+    AttestationCount = 25,
+    ReserveRatioFloor = 26,
+    NavSigners = 27,
+    NavNonce = 28,
+    TimelockDelay = 29,
+    TimelockQueue = 30,
+    LiquidityBuffer = 31,
+    OracleMaxDeviation = 32,
+    CollateralHeartbeat = 33,
 }
 impl TryFromVal<Env, DataKey> for Val {
     type Error = ConversionError;
@@ -20,48 +57,106 @@ impl TryFromVal<Env, DataKey> for Val {
     }
 }
 
-pub fn get_etf_market_value(e: &Env) -> i128 {
+/// Keys for entries that are stored once per id, address, role or operation.
+///
+/// Roles, pause flags, collateral and the redemption queue totals are
+/// contract-wide and live in instance
+/// storage with the `DataKey` entries; the rest are kept in persistent
+/// storage through `get_record` and `set_record`.
+#[derive(Clone)]
+#[contracttype]
+pub enum RecordKey {
+    Redemption(u64),
+    RoleMembers(Role),
+    Paused(Operation),
+    Investor(Address),
+    RedemptionBuckets(Address),
+    GlobalRedemptionBuckets,
+    NavSnapshot(u32),
+    Collateral(Address),
+    AssetReserves(Address),
+    Attestation(u32),
+    TimelockOperation(BytesN<32>),
+    QueuedRedemptions(Address),
+    CarriedRate(Address),
+}
+
+pub fn bump_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .bump(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn bump_record(e: &Env, key: &RecordKey) {
     e.storage()
         .persistent()
+        .bump(key, RECORD_LIFETIME_THRESHOLD, RECORD_BUMP_AMOUNT);
+}
+
+/// Reads a persistent record, extending its TTL if it exists.
+pub fn get_record<V: TryFromVal<Env, Val>>(e: &Env, key: &RecordKey) -> Option<V> {
+    let value = e.storage().persistent().get(key);
+    if value.is_some() {
+        bump_record(e, key);
+    }
+    value
+}
+
+pub fn set_record<V: IntoVal<Env, Val>>(e: &Env, key: &RecordKey, value: &V) {
+    e.storage().persistent().set(key, value);
+    bump_record(e, key);
+}
+
+pub fn remove_record(e: &Env, key: &RecordKey) {
+    e.storage().persistent().remove(key)
+}
+
+pub fn get_etf_market_value(e: &Env) -> i128 {
+    e.storage()
+        .instance()
         .get(&DataKey::MarketETFPrice)
         .unwrap()
 }
 
 pub fn get_token_usdc(e: &Env) -> Address {
-    e.storage().persistent().get(&DataKey::TokenUSDC).unwrap()
+    e.storage().instance().get(&DataKey::TokenUSDC).unwrap()
 }
 
 pub fn get_token_xusg(e: &Env) -> Address {
-    e.storage().persistent().get(&DataKey::TokenXUSG).unwrap()
+    e.storage().instance().get(&DataKey::TokenXUSG).unwrap()
 }
 
 pub fn get_total_xusg(e: &Env) -> i128 {
-    e.storage().persistent().get(&DataKey::TotalXUSG).unwrap()
+    e.storage().instance().get(&DataKey::TotalXUSG).unwrap()
 }
 
-pub fn add_to_cash_reserves(e: &Env, amount: i128) -> i128 {
-    require_positive(amount);
-    let cash_reserves = get_cash_reserves(e);
-    let new_cash = cash_reserves + amount;
+pub fn add_to_cash_reserves(e: &Env, amount: i128) -> Result<i128, ExcellarError> {
+    require_positive(amount)?;
+    let new_cash = checked_add(get_cash_reserves(e), amount)?;
     set_cash_reserves(e, new_cash);
-    new_cash
+    Ok(new_cash)
 }
-pub fn subtract_from_cash_reserves(e: &Env, amount: i128) -> i128 {
-    require_positive(amount);
-    let cash_reserves = get_cash_reserves(e);
-    let new_cash = cash_reserves - amount;
-    set_cash_reserves(e, cash_reserves - amount);
-    new_cash
+pub fn subtract_from_cash_reserves(e: &Env, amount: i128) -> Result<i128, ExcellarError> {
+    require_positive(amount)?;
+    let new_cash = checked_sub(get_cash_reserves(e), amount)?;
+    set_cash_reserves(e, new_cash);
+    Ok(new_cash)
 }
 pub fn get_cash_reserves(e: &Env) -> i128 {
-    e.storage()
-        .persistent()
-        .get(&DataKey::ReservesCash)
-        .unwrap()
+    e.storage().instance().get(&DataKey::ReservesCash).unwrap()
 }
 
 pub fn get_fees(e: &Env) -> i128 {
-    e.storage().persistent().get(&DataKey::Fees).unwrap()
+    e.storage().instance().get(&DataKey::Fees).unwrap()
+}
+
+pub fn get_price_decimals(e: &Env) -> u32 {
+    e.storage().instance().get(&DataKey::PriceDecimals).unwrap()
+}
+
+/// The fixed-point representation of a price of exactly 1.
+pub fn price_unit(e: &Env) -> i128 {
+    10_i128.pow(get_price_decimals(e))
 }
 
 pub fn get_balance(e: &Env, contract: Address) -> i128 {
@@ -73,41 +168,59 @@ pub fn get_balance_usdc(e: &Env) -> i128 {
 }
 
 pub fn set_token_usdc(e: &Env, contract: Address) {
-    e.storage().persistent().set(&DataKey::TokenUSDC, &contract);
+    e.storage().instance().set(&DataKey::TokenUSDC, &contract);
 }
 
 pub fn set_token_xusg(e: &Env, contract: Address) {
-    e.storage().persistent().set(&DataKey::TokenXUSG, &contract);
+    e.storage().instance().set(&DataKey::TokenXUSG, &contract);
 }
 
 pub fn set_total_xusg(e: &Env, amount: i128) {
-    e.storage().persistent().set(&DataKey::TotalXUSG, &amount)
+    e.storage().instance().set(&DataKey::TotalXUSG, &amount)
 }
 
 pub fn set_cash_reserves(e: &Env, amount: i128) {
-    e.storage()
-        .persistent()
-        .set(&DataKey::ReservesCash, &amount)
+    e.storage().instance().set(&DataKey::ReservesCash, &amount)
 }
 
 pub fn set_etf_market_value(e: &Env, price: i128) {
-    e.storage()
-        .persistent()
-        .set(&DataKey::MarketETFPrice, &price)
+    e.storage().instance().set(&DataKey::MarketETFPrice, &price)
 }
 
 pub fn set_fees(e: &Env, price: i128) {
-    e.storage().persistent().set(&DataKey::Fees, &price)
+    e.storage().instance().set(&DataKey::Fees, &price)
+}
+
+pub fn set_price_decimals(e: &Env, decimals: u32) {
+    e.storage()
+        .instance()
+        .set(&DataKey::PriceDecimals, &decimals)
 }
 
 pub fn set_admin(e: &Env, admin: Address) {
-    e.storage().persistent().set(&DataKey::Admin, &admin)
+    e.storage().instance().set(&DataKey::Admin, &admin)
 }
 
-pub fn require_admin(e: &Env) {
-    e.storage()
-        .persistent()
-        .get::<DataKey, Address>(&DataKey::Admin)
-        .unwrap()
-        .require_auth();
+pub fn get_admin(e: &Env) -> Address {
+    e.storage().instance().get(&DataKey::Admin).unwrap()
+}
+
+pub fn is_initialized(e: &Env) -> bool {
+    e.storage().instance().has(&DataKey::Admin)
+}
+
+/// Fails before `initialize` and otherwise keeps the instance alive; every
+/// entrypoint goes through this or `require_admin`.
+pub fn require_initialized(e: &Env) -> Result<(), AccessError> {
+    if !is_initialized(e) {
+        return Err(AccessError::NotInitialized);
+    }
+    bump_instance(e);
+    Ok(())
+}
+
+pub fn require_admin(e: &Env) -> Result<(), AccessError> {
+    require_initialized(e)?;
+    get_admin(e).require_auth();
+    Ok(())
 }
@@ -1,14 +1,61 @@
 #![cfg(test)]
 extern crate std;
 
+use crate::admin::PendingAdmin;
+use crate::allowlist::{Investor, InvestorCategory};
+use crate::attestation::{Attestation, ReserveRatio};
+use crate::collateral::{Collateral, ConversionRate};
+use crate::custodian::{NavPayload, NavSignature, NavSigners};
+use crate::error::{ExcellarError, GovernanceError};
+use crate::events::{
+    AdminWithdrawEvent, CashReservesEvent, EtfMarketValueEvent, FeesCollectedEvent,
+    InitializedEvent, ManagementFeeEvent,
+};
+use crate::fees::TransactionFees;
+use crate::limits::{Limits, LiquidityBuffer};
+use crate::nav::NavSnapshot;
+use crate::oracle::{Asset, OracleConfig, PriceData};
+use crate::pause::Operation;
+use crate::receipt::{DepositReceipt, WithdrawReceipt};
+use crate::redemption::{RedemptionPriceMode, RedemptionRequest};
+use crate::roles::Role;
+use crate::storage::{DataKey, RecordKey, DAY_IN_LEDGERS};
+use crate::timelock::{
+    AdminWithdrawal, CollateralChange, RoleChange, ScheduledOperation, TimelockCall,
+    MAX_TIMELOCK_DELAY,
+};
+use crate::token::ShareToken;
+use crate::upgrade::{SCHEMA_VERSION, V1_MIGRATED_PRICE_DECIMALS};
 use crate::{token, ExcellarTokenizerClient};
 
-use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, IntoVal, Symbol};
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events, Ledger},
+    vec, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
+};
+
+const PRICE_DECIMALS: u32 = 7;
+/// A price of exactly 1 at `PRICE_DECIMALS`.
+const PRICE_UNIT: i128 = 10_i128.pow(PRICE_DECIMALS);
+/// One USDC with the Stellar asset contract's 7 decimals. It equals
+/// `PRICE_UNIT` only because the tests price XUSG with 7 decimals too.
+const USDC_UNIT: i128 = 10_i128.pow(7);
+/// One USDC as reported by `MockOracle`, which uses 14 decimals.
+const ORACLE_UNIT: i128 = 10_i128.pow(14);
 
 fn create_token_contract<'a>(e: &Env, admin: &Address) -> token::Client<'a> {
     token::Client::new(e, &e.register_stellar_asset_contract(admin.clone()))
 }
 
+fn xusg_share(e: &Env) -> ShareToken {
+    ShareToken {
+        name: String::from_str(e, "Excellar Mint"),
+        symbol: String::from_str(e, "XUSG"),
+        decimals: 7,
+    }
+}
+
 fn create_tokenizer_contract<'a>(
     e: &Env,
     token_wasm_hash: &BytesN<32>,
@@ -19,10 +66,130 @@ fn create_tokenizer_contract<'a>(
         e,
         &e.register_contract(None, crate::contract::ExcellarTokenizer {}),
     );
-    tokenizer.initialize(token_wasm_hash, token_a, admin);
+    tokenizer.initialize(
+        token_wasm_hash,
+        token_a,
+        admin,
+        &PRICE_DECIMALS,
+        &xusg_share(e),
+    );
     tokenizer
 }
 
+fn create_investor(e: &Env, tokenizer: &ExcellarTokenizerClient, compliance: &Address) -> Address {
+    let investor = Address::random(e);
+    tokenizer.add_investors(
+        compliance,
+        &vec![
+            e,
+            Investor {
+                account: investor.clone(),
+                category: InvestorCategory::Retail,
+                expires_at: u64::MAX,
+            },
+        ],
+    );
+    investor
+}
+
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(e: Env, price: i128, timestamp: u64) {
+        e.storage()
+            .instance()
+            .set(&symbol_short!("price"), &PriceData { price, timestamp });
+    }
+
+    pub fn set_decimals(e: Env, decimals: u32) {
+        e.storage()
+            .instance()
+            .set(&symbol_short!("decimals"), &decimals);
+    }
+
+    pub fn decimals(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&symbol_short!("decimals"))
+            .unwrap_or(14)
+    }
+
+    pub fn lastprice(e: Env, _asset: Asset) -> Option<PriceData> {
+        e.storage().instance().get(&symbol_short!("price"))
+    }
+}
+
+fn create_oracle_contract<'a>(e: &Env) -> MockOracleClient<'a> {
+    MockOracleClient::new(e, &e.register_contract(None, MockOracle {}))
+}
+
+/// A contract holding USDC of its own that subscribes for its users.
+#[contract]
+pub struct MockRouter;
+
+#[contractimpl]
+impl MockRouter {
+    pub fn approve(e: Env, asset: Address, tokenizer: Address, amount: i128) {
+        token::Client::new(&e, &asset).approve(
+            &e.current_contract_address(),
+            &tokenizer,
+            &amount,
+            &(e.ledger().sequence() + 1_000),
+        );
+    }
+
+    pub fn subscribe(
+        e: Env,
+        tokenizer: Address,
+        asset: Address,
+        beneficiary: Address,
+        amount: i128,
+    ) -> DepositReceipt {
+        ExcellarTokenizerClient::new(&e, &tokenizer).deposit_from(
+            &e.current_contract_address(),
+            &beneficiary,
+            &asset,
+            &amount,
+        )
+    }
+}
+
+fn create_router_contract<'a>(e: &Env) -> MockRouterClient<'a> {
+    MockRouterClient::new(e, &e.register_contract(None, MockRouter {}))
+}
+
+fn sign_nav(
+    e: &Env,
+    tokenizer: &ExcellarTokenizerClient,
+    key: &SigningKey,
+    payload: &NavPayload,
+) -> NavSignature {
+    let message: std::vec::Vec<u8> = tokenizer.signed_nav_message(payload).iter().collect();
+    NavSignature {
+        public_key: BytesN::from_array(e, &key.verifying_key().to_bytes()),
+        signature: BytesN::from_array(e, &key.sign(&message).to_bytes()),
+    }
+}
+
+fn assert_last_event(e: &Env, event: (Address, Vec<Val>, Val)) {
+    let events = e.events().all();
+    assert_eq!(events.slice(events.len() - 1..), vec![e, event]);
+}
+
+/// Events published by `contract` itself, leaving out those of the tokens it
+/// calls into.
+fn contract_events(e: &Env, contract: &Address) -> Vec<(Address, Vec<Val>, Val)> {
+    let mut events = Vec::new(e);
+    for event in e.events().all().iter() {
+        if event.0 == *contract {
+            events.push_back(event);
+        }
+    }
+    events
+}
+
 fn install_token_wasm(e: &Env) -> BytesN<32> {
     soroban_sdk::contractimport!(
         file = "../token/target/wasm32-unknown-unknown/release/excellar_token_contract.wasm"
@@ -30,6 +197,13 @@ fn install_token_wasm(e: &Env) -> BytesN<32> {
     e.install_contract_wasm(WASM)
 }
 
+fn install_tokenizer_wasm(e: &Env) -> BytesN<32> {
+    soroban_sdk::contractimport!(
+        file = "target/wasm32-unknown-unknown/release/excellar_tokenizer_contract.wasm"
+    );
+    e.install_contract_wasm(WASM)
+}
+
 #[test]
 fn test_multi_user_deposit() {
     let e = Env::default();
@@ -37,56 +211,63 @@ fn test_multi_user_deposit() {
 
     let admin1 = Address::random(&e);
     let token_usdc = create_token_contract(&e, &admin1);
-    let user1 = Address::random(&e);
-    let user2 = Address::random(&e);
     let tokenizer =
         create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
 
     token_usdc.mint(&user1, &40);
     token_usdc.mint(&user2, &70);
     assert_eq!(token_usdc.balance(&user1), 40);
     assert_eq!(token_usdc.balance(&user2), 70);
 
-    tokenizer.deposit(&user1, &10);
+    tokenizer.deposit(&user1, &token_usdc.address, &10);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            user1.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    symbol_short!("deposit"),
+                    (&user1, &token_usdc.address, 10_i128).into_val(&e),
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        token_usdc.address.clone(),
+                        symbol_short!("transfer"),
+                        (&user1, &tokenizer.address, 10_i128).into_val(&e),
+                    )),
+                    sub_invocations: std::vec![]
+                }]
+            }
+        )]
+    );
     assert_eq!(tokenizer.cash_reserves(), 10);
-    assert_eq!(tokenizer.price(), 1);
+    assert_eq!(tokenizer.price(), PRICE_UNIT);
     assert_eq!(tokenizer.etf_market_value(), 0);
 
-    // assert_eq!(
-    //     e.auths(),
-    //     [
-    //         (
-    //             user1.clone(),
-    //             tokenizer.address.clone(),
-    //             Symbol::short("deposit"),
-    //             (&user1, 10_i128).into_val(&e)
-    //         ),
-    //         (
-    //             user1.clone(),
-    //             token_usdc.address.clone(),
-    //             Symbol::short("transfer"),
-    //             (&user1, &tokenizer.address, 10_i128).into_val(&e)
-    //         )
-    //     ]
-    // );
-
-    tokenizer.deposit(&user2, &20);
+    tokenizer.deposit(&user2, &token_usdc.address, &20);
     assert_eq!(
         e.auths(),
-        [
-            (
-                user2.clone(),
-                tokenizer.address.clone(),
-                Symbol::short("deposit"),
-                (&user2, 20_i128).into_val(&e)
-            ),
-            (
-                user2.clone(),
-                token_usdc.address.clone(),
-                Symbol::short("transfer"),
-                (&user2, &tokenizer.address, 20_i128).into_val(&e)
-            ),
-        ]
+        std::vec![(
+            user2.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    symbol_short!("deposit"),
+                    (&user2, &token_usdc.address, 20_i128).into_val(&e),
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        token_usdc.address.clone(),
+                        symbol_short!("transfer"),
+                        (&user2, &tokenizer.address, 20_i128).into_val(&e),
+                    )),
+                    sub_invocations: std::vec![]
+                }]
+            }
+        )]
     );
 
     assert_eq!(tokenizer.balance(&user1), 10);
@@ -95,7 +276,7 @@ fn test_multi_user_deposit() {
     assert_eq!(token_usdc.balance(&tokenizer.address), 30);
 
     assert_eq!(tokenizer.cash_reserves(), 30);
-    assert_eq!(tokenizer.price(), 1);
+    assert_eq!(tokenizer.price(), PRICE_UNIT);
     assert_eq!(tokenizer.etf_market_value(), 0);
 
     assert_eq!(tokenizer.balance(&user2), 20);
@@ -103,24 +284,29 @@ fn test_multi_user_deposit() {
     assert_eq!(token_usdc.balance(&user2), 50);
     assert_eq!(token_usdc.balance(&tokenizer.address), 30);
 
-    tokenizer.withdraw(&user1, &7);
-    // assert_eq!(
-    //     e.auths(),
-    //     [
-    //         (
-    //             user1.clone(),
-    //             tokenizer.address.clone(),
-    //             Symbol::short("withdraw"),
-    //             (&user1, 7_i128).into_val(&e)
-    //         ),
-    //         (
-    //             user1.clone(),
-    //             tokenizer.address.clone(),
-    //             Symbol::short("transfer"),
-    //             (&user1, &tokenizer.address, 7_i128).into_val(&e)
-    //         )
-    //     ]
-    // );
+    let xusg_id = tokenizer.xusg_id();
+    tokenizer.withdraw(&user1, &token_usdc.address, &7);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            user1.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    symbol_short!("withdraw"),
+                    (&user1, &token_usdc.address, 7_i128).into_val(&e),
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        xusg_id.clone(),
+                        symbol_short!("transfer"),
+                        (&user1, &tokenizer.address, 7_i128).into_val(&e),
+                    )),
+                    sub_invocations: std::vec![]
+                }]
+            }
+        )]
+    );
 
     assert_eq!(token_usdc.balance(&user1), 37);
     assert_eq!(tokenizer.balance(&user1), 3);
@@ -129,7 +315,7 @@ fn test_multi_user_deposit() {
     assert_eq!(token_usdc.balance(&tokenizer.address), 23);
     assert_eq!(tokenizer.balance(&tokenizer.address), 0);
     assert_eq!(tokenizer.cash_reserves(), 23);
-    assert_eq!(tokenizer.price(), 1);
+    assert_eq!(tokenizer.price(), PRICE_UNIT);
     assert_eq!(tokenizer.etf_market_value(), 0);
 }
 
@@ -142,7 +328,7 @@ fn admin_withdraw_works() {
     let tokenizer =
         create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
 
-    tokenizer.set_etf_market_value(&100);
+    tokenizer.set_etf_market_value(&admin1, &100);
 }
 
 #[test]
@@ -154,7 +340,7 @@ fn test_set_get_etf_market_value() {
     let tokenizer =
         create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
 
-    tokenizer.set_etf_market_value(&100);
+    tokenizer.set_etf_market_value(&admin1, &100);
     assert_eq!(tokenizer.etf_market_value(), 100);
 }
 
@@ -167,22 +353,68 @@ fn test_set_cash_reserves() {
     let tokenizer =
         create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
 
-    tokenizer.set_cash_reserves(&100);
+    tokenizer.set_cash_reserves(&admin1, &100);
     assert_eq!(tokenizer.cash_reserves(), 100);
 }
 
 #[test]
-fn test_set_fees() {
+fn test_management_fee_accrual() {
     let e = Env::default();
     e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
     let admin1 = Address::random(&e);
     let token_usdc = create_token_contract(&e, &admin1);
     let tokenizer =
         create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
 
-    tokenizer.set_etf_market_value(&200);
-    tokenizer.set_fees(&100);
-    assert_eq!(tokenizer.fees(), 100);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &2_000_000);
+    tokenizer.deposit(&user1, &token_usdc.address, &1_000_000);
+
+    assert_eq!(
+        tokenizer.try_set_management_fee(&10_001),
+        Err(Ok(ExcellarError::InvalidFeeRate))
+    );
+    tokenizer.set_management_fee(&100);
+    assert_eq!(tokenizer.management_fee(), 100);
+
+    // 1% a year on 1,000,000 of assets.
+    e.ledger()
+        .with_mut(|li| li.timestamp = 1_000 + 365 * 86_400 / 2);
+    assert_eq!(tokenizer.fees(), 5_000);
+    assert_eq!(tokenizer.price(), 9_950_000);
+
+    // The deposit books the accrued fee and is priced net of it.
+    assert_eq!(
+        tokenizer
+            .deposit(&user1, &token_usdc.address, &995_000)
+            .xusg,
+        1_000_000
+    );
+    e.ledger()
+        .with_mut(|li| li.timestamp = 1_000 + 365 * 86_400);
+    // A further 1% * 1/2 year on 1,990,000.
+    assert_eq!(tokenizer.fees(), 5_000 + 9_950);
+
+    // Changing the rate settles what was earned at the old one.
+    tokenizer.set_management_fee(&0);
+    e.ledger()
+        .with_mut(|li| li.timestamp = 1_000 + 2 * 365 * 86_400);
+    assert_eq!(tokenizer.fees(), 14_950);
+
+    let treasurer = Address::random(&e);
+    assert_eq!(
+        tokenizer.try_collect_fees(&treasurer, &treasurer),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+    tokenizer.grant_role(&Role::Treasurer, &treasurer);
+    let price = tokenizer.price();
+    assert_eq!(tokenizer.collect_fees(&treasurer, &treasurer), 14_950);
+    assert_eq!(token_usdc.balance(&treasurer), 14_950);
+    assert_eq!(tokenizer.fees(), 0);
+    assert_eq!(tokenizer.cash_reserves(), 1_995_000 - 14_950);
+    assert_eq!(tokenizer.price(), price);
+    assert_eq!(tokenizer.collect_fees(&treasurer, &treasurer), 0);
 }
 
 #[test]
@@ -194,19 +426,19 @@ fn test_withdraw_admin() {
     let tokenizer =
         create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
 
-    let user1 = Address::random(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
 
     token_usdc.mint(&user1, &40);
-    tokenizer.deposit(&user1, &10);
+    tokenizer.deposit(&user1, &token_usdc.address, &10);
 
-    tokenizer.withdraw_admin(&admin1, &10);
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &10);
     assert_eq!(tokenizer.balance(&user1), 10);
     assert_eq!(tokenizer.balance(&tokenizer.address), 0);
     assert_eq!(tokenizer.cash_reserves(), 10);
     assert_eq!(tokenizer.total(), 10);
     assert_eq!(tokenizer.fees(), 0);
     assert_eq!(tokenizer.etf_market_value(), 0);
-    assert_eq!(tokenizer.price(), 1);
+    assert_eq!(tokenizer.price(), PRICE_UNIT);
     assert_eq!(token_usdc.balance(&user1), 30);
     assert_eq!(token_usdc.balance(&tokenizer.address), 0);
 }
@@ -220,18 +452,2948 @@ fn test_price_change() {
     let tokenizer =
         create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
 
-    let user1 = Address::random(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
 
     token_usdc.mint(&user1, &100);
-    tokenizer.deposit(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
 
-    tokenizer.set_etf_market_value(&150);
-    tokenizer.set_cash_reserves(&50);
-    tokenizer.set_fees(&10);
-    assert_eq!(tokenizer.cash_reserves(), 50);
+    tokenizer.set_etf_market_value(&admin1, &150);
+    tokenizer.set_cash_reserves(&admin1, &40);
+    assert_eq!(tokenizer.cash_reserves(), 40);
     assert_eq!(tokenizer.total(), 100);
-    assert_eq!(tokenizer.fees(), 10);
+    assert_eq!(tokenizer.fees(), 0);
     assert_eq!(tokenizer.etf_market_value(), 150);
-    // (150 + 50 - 10) / 100 = 1.9 = 1 because of no-std rounding
-    assert_eq!(tokenizer.price(), 1);
+    // (150 + 40 - 0) / 100 = 1.9
+    assert_eq!(tokenizer.price(), 19_000_000);
+}
+
+#[test]
+fn test_deposit_withdraw_at_fractional_price() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+
+    token_usdc.mint(&user1, &100);
+    token_usdc.mint(&user2, &1_000);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    tokenizer.set_etf_market_value(&admin1, &150);
+    tokenizer.set_cash_reserves(&admin1, &40);
+    assert_eq!(tokenizer.price(), 19_000_000);
+
+    // 190 / 1.9 = 100
+    assert_eq!(
+        tokenizer.deposit(&user2, &token_usdc.address, &190).xusg,
+        100
+    );
+    assert_eq!(tokenizer.balance(&user2), 100);
+    assert_eq!(tokenizer.price(), 19_000_000);
+
+    // 7 * 1.9 = 13.3, rounded down in favour of the remaining holders
+    assert_eq!(tokenizer.withdraw(&user1, &token_usdc.address, &7).net, 13);
+    assert_eq!(token_usdc.balance(&user1), 13);
+    assert_eq!(tokenizer.balance(&user1), 93);
+
+    // 100 * 1.9 = 190
+    assert_eq!(
+        tokenizer.withdraw(&user2, &token_usdc.address, &100).net,
+        190
+    );
+    assert_eq!(token_usdc.balance(&user2), 1_000);
+}
+
+#[test]
+fn test_price_decimals() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer = ExcellarTokenizerClient::new(
+        &e,
+        &e.register_contract(None, crate::contract::ExcellarTokenizer {}),
+    );
+    tokenizer.initialize(
+        &install_token_wasm(&e),
+        &token_usdc.address,
+        &admin1,
+        &18,
+        &xusg_share(&e),
+    );
+    assert_eq!(tokenizer.price_decimals(), 18);
+    assert_eq!(tokenizer.price(), 1_000_000_000_000_000_000);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &300);
+    tokenizer.deposit(&user1, &token_usdc.address, &300);
+    tokenizer.set_etf_market_value(&admin1, &100);
+    // (100 + 300) / 300 = 1.333...
+    assert_eq!(tokenizer.price(), 1_333_333_333_333_333_333);
+}
+
+#[test]
+fn test_share_token_metadata() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer = ExcellarTokenizerClient::new(
+        &e,
+        &e.register_contract(None, crate::contract::ExcellarTokenizer {}),
+    );
+    let mut share = ShareToken {
+        name: String::from_str(&e, "Excellar Euro Mint"),
+        symbol: String::from_str(&e, "XEUR"),
+        decimals: 6,
+    };
+
+    // Shares are minted in collateral units, so they take its decimals.
+    assert_eq!(
+        tokenizer.try_initialize(
+            &install_token_wasm(&e),
+            &token_usdc.address,
+            &admin1,
+            &PRICE_DECIMALS,
+            &share,
+        ),
+        Err(Ok(ExcellarError::ShareDecimalsMismatch))
+    );
+
+    share.decimals = 7;
+    tokenizer.initialize(
+        &install_token_wasm(&e),
+        &token_usdc.address,
+        &admin1,
+        &PRICE_DECIMALS,
+        &share,
+    );
+
+    let xusg = token::Client::new(&e, &tokenizer.xusg_id());
+    assert_eq!(xusg.name(), share.name);
+    assert_eq!(xusg.symbol(), share.symbol);
+    assert_eq!(xusg.decimals(), 7);
+}
+
+#[test]
+fn test_price_decimals_too_large() {
+    let e = Env::default();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer = ExcellarTokenizerClient::new(
+        &e,
+        &e.register_contract(None, crate::contract::ExcellarTokenizer {}),
+    );
+    assert_eq!(
+        tokenizer.try_initialize(
+            &install_token_wasm(&e),
+            &token_usdc.address,
+            &admin1,
+            &19,
+            &xusg_share(&e)
+        ),
+        Err(Ok(ExcellarError::InvalidPriceDecimals))
+    );
+}
+
+#[test]
+fn test_redemption_queue_fulfilled_when_usdc_returns() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    token_usdc.mint(&user2, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.deposit(&user2, &token_usdc.address, &100);
+
+    // All USDC goes off-chain to buy T-bills.
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &200);
+    assert_eq!(
+        tokenizer.try_withdraw(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::InsufficientLiquidity))
+    );
+
+    assert_eq!(
+        tokenizer.request_redeem(&user1, &token_usdc.address, &60),
+        0
+    );
+    assert_eq!(
+        tokenizer.request_redeem(&user2, &token_usdc.address, &50),
+        1
+    );
+    assert_eq!(tokenizer.balance(&user1), 40);
+    assert_eq!(tokenizer.balance(&tokenizer.address), 110);
+    assert_eq!(tokenizer.total(), 200);
+
+    let request = tokenizer.redemption(&0);
+    assert_eq!(request.owner, user1);
+    assert_eq!(request.xusg_amount, 60);
+    assert_eq!(request.locked_price, None);
+
+    // Nothing can be paid out yet.
+    assert_eq!(tokenizer.fulfil_redemptions(&10), 0);
+    assert_eq!(tokenizer.redemption_queue_head(), 0);
+
+    // The first T-bills mature, but not enough for both requests.
+    token_usdc.mint(&tokenizer.address, &80);
+    assert_eq!(tokenizer.fulfil_redemptions(&10), 1);
+    assert_eq!(tokenizer.redemption_queue_head(), 1);
+    assert_eq!(token_usdc.balance(&user1), 60);
+    assert_eq!(
+        tokenizer.try_redemption(&0),
+        Err(Ok(ExcellarError::RedemptionNotFound))
+    );
+
+    token_usdc.mint(&tokenizer.address, &120);
+    assert_eq!(tokenizer.fulfil_redemptions(&10), 1);
+    assert_eq!(tokenizer.redemption_queue_head(), 2);
+    assert_eq!(token_usdc.balance(&user2), 50);
+    assert_eq!(tokenizer.balance(&tokenizer.address), 0);
+    assert_eq!(tokenizer.total(), 90);
+    assert_eq!(tokenizer.cash_reserves(), 90);
+}
+
+#[test]
+fn test_redemption_price_locked_at_request() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &100);
+
+    assert_eq!(
+        tokenizer.redemption_price_mode(),
+        RedemptionPriceMode::AtFulfilment
+    );
+    tokenizer.set_redemption_price_mode(&RedemptionPriceMode::AtRequest);
+
+    let id = tokenizer.request_redeem(&user1, &token_usdc.address, &50);
+    assert_eq!(tokenizer.redemption(&id).locked_price, Some(PRICE_UNIT));
+
+    // The NAV doubles before the T-bills mature.
+    tokenizer.set_etf_market_value(&admin1, &100);
+    assert_eq!(tokenizer.price(), 2 * PRICE_UNIT);
+
+    token_usdc.mint(&tokenizer.address, &100);
+    assert_eq!(tokenizer.fulfil_redemptions(&1), 1);
+    assert_eq!(token_usdc.balance(&user1), 50);
+}
+
+#[test]
+fn test_redemption_price_at_fulfilment() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &100);
+
+    tokenizer.request_redeem(&user1, &token_usdc.address, &50);
+    tokenizer.set_etf_market_value(&admin1, &100);
+
+    token_usdc.mint(&tokenizer.address, &100);
+    assert_eq!(tokenizer.fulfil_redemptions(&1), 1);
+    assert_eq!(token_usdc.balance(&user1), 100);
+}
+
+#[test]
+fn test_cancel_redemption() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    token_usdc.mint(&user2, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.deposit(&user2, &token_usdc.address, &100);
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &200);
+
+    let first = tokenizer.request_redeem(&user1, &token_usdc.address, &30);
+    let second = tokenizer.request_redeem(&user2, &token_usdc.address, &20);
+    assert_eq!(
+        tokenizer.try_cancel_redeem(&user2, &first),
+        Err(Ok(ExcellarError::NotRedemptionOwner))
+    );
+
+    assert_eq!(tokenizer.cancel_redeem(&user1, &first), 30);
+    assert_eq!(tokenizer.balance(&user1), 100);
+    assert_eq!(
+        tokenizer.try_cancel_redeem(&user1, &first),
+        Err(Ok(ExcellarError::RedemptionNotFound))
+    );
+
+    // The cancelled slot is skipped and the next request is paid.
+    token_usdc.mint(&tokenizer.address, &20);
+    assert_eq!(tokenizer.fulfil_redemptions(&2), 1);
+    assert_eq!(tokenizer.redemption_queue_head(), second + 1);
+    assert_eq!(token_usdc.balance(&user2), 20);
+}
+
+#[test]
+fn test_queue_paid_before_direct_withdrawals() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+    let user3 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    token_usdc.mint(&user2, &100);
+    token_usdc.mint(&user3, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.deposit(&user2, &token_usdc.address, &100);
+    tokenizer.deposit(&user3, &token_usdc.address, &100);
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &250);
+
+    // 50 USDC is left, and the queue is owed 40 of it: 30 at the current
+    // price and 10 locked at request.
+    tokenizer.request_redeem(&user1, &token_usdc.address, &30);
+    tokenizer.set_redemption_price_mode(&RedemptionPriceMode::AtRequest);
+    tokenizer.request_redeem(&user2, &token_usdc.address, &10);
+    assert_eq!(tokenizer.admin_withdrawable(&token_usdc.address), 10);
+
+    // A later direct withdrawal can't take what the queue is owed, and
+    // neither can the treasurer.
+    assert_eq!(
+        tokenizer.try_withdraw(&user3, &token_usdc.address, &20),
+        Err(Ok(ExcellarError::InsufficientLiquidity))
+    );
+    assert_eq!(
+        tokenizer.try_withdraw_admin(&admin1, &admin1, &token_usdc.address, &20),
+        Err(Ok(ExcellarError::InsufficientLiquidity))
+    );
+    assert_eq!(tokenizer.max_withdraw(&user3), 10);
+    tokenizer.withdraw(&user3, &token_usdc.address, &10);
+    assert_eq!(token_usdc.balance(&tokenizer.address), 40);
+
+    // Once the queue has been paid, USDC coming back is free to withdraw.
+    assert_eq!(tokenizer.fulfil_redemptions(&10), 2);
+    assert_eq!(token_usdc.balance(&user1), 30);
+    assert_eq!(token_usdc.balance(&user2), 10);
+    token_usdc.mint(&tokenizer.address, &20);
+    tokenizer.withdraw(&user3, &token_usdc.address, &20);
+    assert_eq!(token_usdc.balance(&user3), 30);
+}
+
+#[test]
+fn test_etf_market_value_from_oracle() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = create_oracle_contract(&e);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &(1_000 * USDC_UNIT));
+    tokenizer.deposit(&user1, &token_usdc.address, &(1_000 * USDC_UNIT));
+
+    assert_eq!(
+        tokenizer.try_update_etf_market_value(),
+        Err(Ok(ExcellarError::OracleNotConfigured))
+    );
+
+    let asset = Asset::Other(symbol_short!("XUSGETF"));
+    tokenizer.set_oracle(&oracle.address, &asset);
+    assert_eq!(tokenizer.oracle().unwrap().asset, asset);
+    assert_eq!(
+        tokenizer.try_update_etf_market_value(),
+        Err(Ok(ExcellarError::OraclePriceUnavailable))
+    );
+
+    // 1,000 USDC reported with the oracle's 14 decimals.
+    e.ledger().with_mut(|li| li.timestamp = 1_100);
+    oracle.set_price(&(1_000 * ORACLE_UNIT), &1_090);
+    assert_eq!(tokenizer.update_etf_market_value(), 1_000 * USDC_UNIT);
+    assert_eq!(tokenizer.etf_market_value(), 1_000 * USDC_UNIT);
+    assert_eq!(tokenizer.nav_updated_at(), 1_090);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "etf_market_value"), oracle.address.clone()).into_val(&e),
+            EtfMarketValueEvent {
+                value: 1_000 * USDC_UNIT,
+                updated_at: 1_090,
+            }
+            .into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.price(), 2 * PRICE_UNIT);
+    assert_eq!(tokenizer.nav_history_len(), 1);
+
+    // Reading the same oracle value again adds no history or event.
+    let events = e.events().all().len();
+    tokenizer.update_etf_market_value();
+    assert_eq!(e.events().all().len(), events);
+    assert_eq!(tokenizer.nav_history_len(), 1);
+
+    // A reading older than the current NAV is skipped.
+    oracle.set_price(&(3_000 * ORACLE_UNIT), &1_000);
+    assert_eq!(tokenizer.update_etf_market_value(), 1_000 * USDC_UNIT);
+    assert_eq!(tokenizer.nav_updated_at(), 1_090);
+    assert_eq!(tokenizer.nav_history_len(), 1);
+
+    // Deposits pick up the latest oracle value on their own.
+    oracle.set_price(&(3_000 * ORACLE_UNIT), &1_100);
+    token_usdc.mint(&user1, &(40 * USDC_UNIT));
+    assert_eq!(
+        tokenizer
+            .deposit(&user1, &token_usdc.address, &(40 * USDC_UNIT))
+            .xusg,
+        10 * USDC_UNIT
+    );
+    assert_eq!(tokenizer.etf_market_value(), 3_000 * USDC_UNIT);
+
+    // A precision beyond what an i128 can scale by fails instead of
+    // trapping.
+    oracle.set_decimals(&39);
+    oracle.set_price(&(3_000 * ORACLE_UNIT), &1_100);
+    assert_eq!(
+        tokenizer.try_update_etf_market_value(),
+        Err(Ok(ExcellarError::ArithmeticOverflow))
+    );
+    oracle.set_decimals(&14);
+
+    tokenizer.remove_oracle();
+    assert_eq!(tokenizer.oracle(), None);
+}
+
+#[test]
+fn test_oracle_max_deviation() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = create_oracle_contract(&e);
+    tokenizer.set_oracle(&oracle.address, &Asset::Other(symbol_short!("XUSGETF")));
+
+    assert_eq!(tokenizer.oracle_max_deviation(), 0);
+    tokenizer.set_oracle_max_deviation(&1_000);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "oracle_max_deviation"),).into_val(&e),
+            1_000_u32.into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.oracle_max_deviation(), 1_000);
+
+    // The first reading has nothing to deviate from.
+    oracle.set_price(&(1_000 * ORACLE_UNIT), &1_000);
+    tokenizer.update_etf_market_value();
+
+    oracle.set_price(&(1_101 * ORACLE_UNIT), &1_100);
+    assert_eq!(
+        tokenizer.try_update_etf_market_value(),
+        Err(Ok(ExcellarError::OracleDeviationExceeded))
+    );
+    assert_eq!(tokenizer.etf_market_value(), 1_000 * USDC_UNIT);
+
+    oracle.set_price(&(900 * ORACLE_UNIT), &1_100);
+    assert_eq!(tokenizer.update_etf_market_value(), 900 * USDC_UNIT);
+}
+
+#[test]
+fn test_stale_price_rejected() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &50);
+
+    tokenizer.set_nav_heartbeat(&3_600);
+    assert_eq!(tokenizer.nav_heartbeat(), 3_600);
+    assert_eq!(tokenizer.nav_updated_at(), 1_000);
+
+    e.ledger().with_mut(|li| li.timestamp = 4_600);
+    tokenizer.deposit(&user1, &token_usdc.address, &10);
+
+    e.ledger().with_mut(|li| li.timestamp = 4_601);
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::StalePrice))
+    );
+    assert_eq!(
+        tokenizer.try_withdraw(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::StalePrice))
+    );
+
+    tokenizer.set_etf_market_value(&admin1, &10);
+    assert_eq!(tokenizer.nav_updated_at(), 4_601);
+    tokenizer.withdraw(&user1, &token_usdc.address, &10);
+}
+
+#[test]
+fn test_stale_oracle_price_rejected() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 10_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = create_oracle_contract(&e);
+    tokenizer.set_oracle(&oracle.address, &Asset::Stellar(Address::random(&e)));
+    tokenizer.set_nav_heartbeat(&600);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+
+    e.ledger().with_mut(|li| li.timestamp = 11_000);
+    oracle.set_price(&ORACLE_UNIT, &10_300);
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &100),
+        Err(Ok(ExcellarError::StalePrice))
+    );
+
+    oracle.set_price(&ORACLE_UNIT, &10_500);
+    assert_eq!(
+        tokenizer.deposit(&user1, &token_usdc.address, &100).xusg,
+        100
+    );
+
+    // A manual update is newer than the oracle's last reading, which is
+    // then skipped rather than holding up trades.
+    tokenizer.set_etf_market_value(&admin1, &(2 * USDC_UNIT));
+    token_usdc.mint(&user1, &(10 * USDC_UNIT));
+    tokenizer.deposit(&user1, &token_usdc.address, &(10 * USDC_UNIT));
+    assert_eq!(tokenizer.etf_market_value(), 2 * USDC_UNIT);
+    assert_eq!(tokenizer.nav_updated_at(), 11_000);
+}
+
+#[test]
+fn test_roles() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let reporter = Address::random(&e);
+    let treasurer = Address::random(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    assert!(tokenizer.has_role(&Role::Reporter, &admin1));
+    assert!(!tokenizer.has_role(&Role::Reporter, &reporter));
+
+    tokenizer.grant_role(&Role::Reporter, &reporter);
+    tokenizer.grant_role(&Role::Treasurer, &treasurer);
+    tokenizer.revoke_role(&Role::Reporter, &admin1);
+    tokenizer.revoke_role(&Role::Treasurer, &admin1);
+    assert_eq!(
+        tokenizer.role_members(&Role::Reporter),
+        vec![&e, reporter.clone()]
+    );
+    assert_eq!(
+        tokenizer.role_members(&Role::Treasurer),
+        vec![&e, treasurer.clone()]
+    );
+
+    // The reporter can only report NAV, the treasurer can only move USDC.
+    tokenizer.set_etf_market_value(&reporter, &100);
+    tokenizer.set_cash_reserves(&reporter, &100);
+    assert_eq!(
+        tokenizer.try_withdraw_admin(&reporter, &reporter, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+    assert_eq!(
+        tokenizer.try_set_etf_market_value(&treasurer, &200),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+    assert_eq!(
+        tokenizer.try_set_cash_reserves(&admin1, &0),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+    tokenizer.withdraw_admin(&treasurer, &treasurer, &token_usdc.address, &10);
+    assert_eq!(token_usdc.balance(&treasurer), 10);
+
+    tokenizer.revoke_role(&Role::Treasurer, &treasurer);
+    assert!(!tokenizer.has_role(&Role::Treasurer, &treasurer));
+    assert_eq!(
+        tokenizer.try_withdraw_admin(&treasurer, &treasurer, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_role_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let reporter = Address::random(&e);
+
+    tokenizer.grant_role(&Role::Reporter, &reporter);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (
+                Symbol::new(&e, "role_granted"),
+                Role::Reporter,
+                reporter.clone(),
+            )
+                .into_val(&e),
+            ().into_val(&e),
+        ),
+    );
+
+    tokenizer.revoke_role(&Role::Reporter, &reporter);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (
+                Symbol::new(&e, "role_revoked"),
+                Role::Reporter,
+                reporter.clone(),
+            )
+                .into_val(&e),
+            ().into_val(&e),
+        ),
+    );
+}
+
+#[test]
+fn test_pause() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let pauser = Address::random(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &50);
+
+    assert_eq!(
+        tokenizer.try_pause(&pauser, &Operation::Deposit),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+    tokenizer.grant_role(&Role::Pauser, &pauser);
+
+    tokenizer.pause(&pauser, &Operation::Deposit);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (symbol_short!("paused"), Operation::Deposit).into_val(&e),
+            pauser.into_val(&e),
+        ),
+    );
+    assert!(tokenizer.is_paused(&Operation::Deposit));
+    assert!(!tokenizer.is_paused(&Operation::Withdraw));
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::DepositsPaused))
+    );
+    tokenizer.withdraw(&user1, &token_usdc.address, &10);
+
+    tokenizer.pause(&pauser, &Operation::Withdraw);
+    assert_eq!(
+        tokenizer.try_withdraw(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::WithdrawalsPaused))
+    );
+    assert_eq!(
+        tokenizer.try_request_redeem(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::WithdrawalsPaused))
+    );
+
+    tokenizer.pause(&pauser, &Operation::AdminWithdraw);
+    assert_eq!(
+        tokenizer.try_withdraw_admin(&admin1, &admin1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::AdminWithdrawalsPaused))
+    );
+
+    tokenizer.unpause(&pauser, &Operation::Deposit);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (symbol_short!("unpaused"), Operation::Deposit).into_val(&e),
+            pauser.into_val(&e),
+        ),
+    );
+    assert!(!tokenizer.is_paused(&Operation::Deposit));
+    tokenizer.deposit(&user1, &token_usdc.address, &10);
+    assert_eq!(tokenizer.balance(&user1), 50);
+}
+
+#[test]
+fn test_allowlist() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let compliance = Address::random(&e);
+    let user1 = Address::random(&e);
+    let user2 = Address::random(&e);
+    let user3 = Address::random(&e);
+    token_usdc.mint(&user1, &100);
+    token_usdc.mint(&user2, &100);
+    token_usdc.mint(&user3, &100);
+
+    assert!(tokenizer.allowlist_enabled());
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::NotAllowlisted))
+    );
+
+    let investors = vec![
+        &e,
+        Investor {
+            account: user1.clone(),
+            category: InvestorCategory::Professional,
+            expires_at: 2_000,
+        },
+        Investor {
+            account: user2.clone(),
+            category: InvestorCategory::Institutional,
+            expires_at: 5_000,
+        },
+    ];
+    assert_eq!(
+        tokenizer.try_add_investors(&compliance, &investors),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+    tokenizer.grant_role(&Role::Compliance, &compliance);
+    tokenizer.add_investors(&compliance, &investors);
+
+    assert_eq!(
+        tokenizer.investor(&user2).unwrap().category,
+        InvestorCategory::Institutional
+    );
+    assert!(tokenizer.is_allowlisted(&user1));
+    assert!(!tokenizer.is_allowlisted(&user3));
+    tokenizer.deposit(&user1, &token_usdc.address, &10);
+    tokenizer.deposit(&user2, &token_usdc.address, &10);
+    assert_eq!(
+        tokenizer.try_deposit(&user3, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::NotAllowlisted))
+    );
+
+    // Onboarding lapses at its expiry timestamp.
+    e.ledger().with_mut(|li| li.timestamp = 2_000);
+    assert!(!tokenizer.is_allowlisted(&user1));
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::AllowlistExpired))
+    );
+    assert_eq!(
+        tokenizer.try_withdraw(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::AllowlistExpired))
+    );
+    tokenizer.withdraw(&user2, &token_usdc.address, &5);
+
+    tokenizer.remove_investors(&compliance, &vec![&e, user1.clone(), user2.clone()]);
+    assert_eq!(tokenizer.investor(&user2), None);
+    assert_eq!(
+        tokenizer.try_withdraw(&user2, &token_usdc.address, &5),
+        Err(Ok(ExcellarError::NotAllowlisted))
+    );
+
+    tokenizer.set_allowlist_enabled(&false);
+    tokenizer.deposit(&user3, &token_usdc.address, &10);
+    assert_eq!(tokenizer.balance(&user3), 10);
+}
+
+#[test]
+fn test_deposit_caps() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &1_000);
+    token_usdc.mint(&user2, &1_000);
+
+    assert_eq!(tokenizer.remaining_deposit_capacity(&user1), i128::MAX);
+
+    tokenizer.set_limits(&Limits {
+        max_total_assets: Some(500),
+        max_position: Some(300),
+        address_daily_redemption: None,
+        global_daily_redemption: None,
+    });
+    assert_eq!(tokenizer.remaining_deposit_capacity(&user1), 300);
+
+    tokenizer.deposit(&user1, &token_usdc.address, &200);
+    assert_eq!(tokenizer.remaining_deposit_capacity(&user1), 100);
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &101),
+        Err(Ok(ExcellarError::PositionCapExceeded))
+    );
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    tokenizer.deposit(&user2, &token_usdc.address, &150);
+    assert_eq!(tokenizer.remaining_deposit_capacity(&user2), 50);
+    assert_eq!(
+        tokenizer.try_deposit(&user2, &token_usdc.address, &60),
+        Err(Ok(ExcellarError::TotalAssetsCapExceeded))
+    );
+
+    // A rising NAV counts against both caps.
+    tokenizer.set_etf_market_value(&admin1, &50);
+    assert_eq!(tokenizer.remaining_deposit_capacity(&user2), 0);
+    assert_eq!(
+        tokenizer.try_deposit(&user2, &token_usdc.address, &1),
+        Err(Ok(ExcellarError::TotalAssetsCapExceeded))
+    );
+}
+
+#[test]
+fn test_daily_redemption_limits() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &1_000);
+    token_usdc.mint(&user2, &1_000);
+    tokenizer.deposit(&user1, &token_usdc.address, &1_000);
+    tokenizer.deposit(&user2, &token_usdc.address, &1_000);
+
+    tokenizer.set_limits(&Limits {
+        max_total_assets: None,
+        max_position: None,
+        address_daily_redemption: Some(100),
+        global_daily_redemption: Some(150),
+    });
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user1), 100);
+
+    tokenizer.withdraw(&user1, &token_usdc.address, &10);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user1), 90);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user2), 100);
+
+    // A second before that withdrawal is a day old, user1 takes the rest of
+    // the limit and user2 the rest of the global one.
+    e.ledger().with_mut(|li| li.timestamp = 1_000 + 86_400 - 1);
+    assert_eq!(
+        tokenizer.try_withdraw(&user1, &token_usdc.address, &91),
+        Err(Ok(ExcellarError::AddressRedemptionLimitExceeded))
+    );
+    tokenizer.withdraw(&user1, &token_usdc.address, &90);
+    tokenizer.withdraw(&user2, &token_usdc.address, &50);
+    assert_eq!(
+        tokenizer.try_withdraw(&user2, &token_usdc.address, &1),
+        Err(Ok(ExcellarError::GlobalRedemptionLimitExceeded))
+    );
+
+    // A second later the first withdrawal is a day old, but the limit rolls:
+    // what was taken in the last 24 hours still counts.
+    e.ledger().with_mut(|li| li.timestamp = 1_000 + 86_400);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user1), 0);
+    assert_eq!(
+        tokenizer.try_withdraw(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::AddressRedemptionLimitExceeded))
+    );
+
+    // The first withdrawal drops out once the hour it was made in is more
+    // than a day behind.
+    e.ledger().with_mut(|li| li.timestamp = 25 * 3_600 - 1);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user1), 0);
+    e.ledger().with_mut(|li| li.timestamp = 25 * 3_600);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user1), 10);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user2), 10);
+    tokenizer.withdraw(&user1, &token_usdc.address, &10);
+    assert_eq!(
+        tokenizer.try_withdraw(&user2, &token_usdc.address, &1),
+        Err(Ok(ExcellarError::GlobalRedemptionLimitExceeded))
+    );
+}
+
+#[test]
+fn test_queued_redemptions_count_against_limits() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &1_000);
+    token_usdc.mint(&user2, &1_000);
+    tokenizer.deposit(&user1, &token_usdc.address, &1_000);
+    tokenizer.deposit(&user2, &token_usdc.address, &1_000);
+
+    tokenizer.set_limits(&Limits {
+        max_total_assets: None,
+        max_position: None,
+        address_daily_redemption: Some(100),
+        global_daily_redemption: Some(150),
+    });
+    tokenizer.request_redeem(&user1, &token_usdc.address, &60);
+    tokenizer.request_redeem(&user2, &token_usdc.address, &60);
+    tokenizer.request_redeem(&user1, &token_usdc.address, &50);
+
+    // The third request would take user1 past 100 for the day.
+    assert_eq!(tokenizer.fulfil_redemptions(&10), 2);
+    assert_eq!(tokenizer.redemption_queue_head(), 2);
+    // Both are now held to what is left of the global limit.
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user1), 30);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user2), 30);
+
+    // A day on, the hour of the first payouts is still counted.
+    e.ledger().with_mut(|li| li.timestamp = 1_000 + 86_400);
+    assert_eq!(tokenizer.fulfil_redemptions(&10), 0);
+    e.ledger().with_mut(|li| li.timestamp = 25 * 3_600);
+    assert_eq!(tokenizer.fulfil_redemptions(&10), 1);
+    assert_eq!(tokenizer.redemption_queue_head(), 3);
+    assert_eq!(tokenizer.remaining_redemption_capacity(&user1), 50);
+    assert_eq!(token_usdc.balance(&user1), 110);
+}
+
+#[test]
+fn test_slippage_and_deadline() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &200);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    // The NAV doubles between signing and execution.
+    tokenizer.set_etf_market_value(&admin1, &100);
+    assert_eq!(
+        tokenizer.try_deposit_checked(&user1, &token_usdc.address, &100, &100, &1_000),
+        Err(Ok(ExcellarError::SlippageExceeded))
+    );
+    assert_eq!(
+        tokenizer.try_deposit_checked(&user1, &token_usdc.address, &100, &50, &999),
+        Err(Ok(ExcellarError::DeadlineExpired))
+    );
+    assert_eq!(
+        tokenizer
+            .deposit_checked(&user1, &token_usdc.address, &100, &50, &1_000)
+            .xusg,
+        50
+    );
+
+    assert_eq!(
+        tokenizer.try_withdraw_checked(&user1, &token_usdc.address, &50, &101, &1_000),
+        Err(Ok(ExcellarError::SlippageExceeded))
+    );
+    assert_eq!(
+        tokenizer.try_withdraw_checked(&user1, &token_usdc.address, &50, &100, &999),
+        Err(Ok(ExcellarError::DeadlineExpired))
+    );
+    assert_eq!(
+        tokenizer
+            .withdraw_checked(&user1, &token_usdc.address, &50, &100, &1_000)
+            .net,
+        100
+    );
+    assert_eq!(tokenizer.balance(&user1), 100);
+}
+
+#[test]
+fn test_deposit_for_and_withdraw_to() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let custodian = Address::random(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let receiver = Address::random(&e);
+    token_usdc.mint(&custodian, &100);
+
+    // The beneficiary, not the payer, has to be onboarded.
+    assert_eq!(
+        tokenizer.try_deposit_for(&user1, &custodian, &token_usdc.address, &60),
+        Err(Ok(ExcellarError::NotAllowlisted))
+    );
+
+    tokenizer.deposit_for(&custodian, &user1, &token_usdc.address, &60);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            custodian.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    Symbol::new(&e, "deposit_for"),
+                    (&custodian, &user1, &token_usdc.address, 60_i128).into_val(&e),
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        token_usdc.address.clone(),
+                        symbol_short!("transfer"),
+                        (&custodian, &tokenizer.address, 60_i128).into_val(&e),
+                    )),
+                    sub_invocations: std::vec![]
+                }]
+            }
+        )]
+    );
+    assert_eq!(token_usdc.balance(&custodian), 40);
+    assert_eq!(tokenizer.balance(&custodian), 0);
+    assert_eq!(tokenizer.balance(&user1), 60);
+
+    let xusg_id = tokenizer.xusg_id();
+    tokenizer.withdraw_to(&user1, &receiver, &token_usdc.address, &25);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            user1.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    Symbol::new(&e, "withdraw_to"),
+                    (&user1, &receiver, &token_usdc.address, 25_i128).into_val(&e),
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        xusg_id.clone(),
+                        symbol_short!("transfer"),
+                        (&user1, &tokenizer.address, 25_i128).into_val(&e),
+                    )),
+                    sub_invocations: std::vec![]
+                }]
+            }
+        )]
+    );
+    assert_eq!(tokenizer.balance(&user1), 35);
+    assert_eq!(token_usdc.balance(&user1), 0);
+    assert_eq!(token_usdc.balance(&receiver), 25);
+    assert_eq!(tokenizer.cash_reserves(), 35);
+}
+
+#[test]
+fn test_deposit_from_allowance() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let router = create_router_contract(&e);
+    token_usdc.mint(&user1, &100);
+    token_usdc.mint(&router.address, &100);
+
+    // Without an approval there is nothing to pull.
+    assert_eq!(
+        tokenizer.try_deposit_from(&user1, &user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::InsufficientAllowance))
+    );
+
+    // An account can approve the tokenizer and then sign only the deposit.
+    token_usdc.approve(&user1, &tokenizer.address, &30, &1_000);
+    tokenizer.deposit_from(&user1, &user1, &token_usdc.address, &30);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            user1.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    Symbol::new(&e, "deposit_from"),
+                    (&user1, &user1, &token_usdc.address, 30_i128).into_val(&e),
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(tokenizer.balance(&user1), 30);
+    assert_eq!(token_usdc.allowance(&user1, &tokenizer.address), 0);
+
+    // A router subscribes for the user out of its own approved USDC, with no
+    // signatures beyond its own invocation.
+    router.approve(&token_usdc.address, &tokenizer.address, &50);
+    e.set_auths(&[]);
+    let receipt = router.subscribe(&tokenizer.address, &token_usdc.address, &user1, &50);
+    assert_eq!(receipt.xusg, 50);
+    assert_eq!(tokenizer.balance(&user1), 80);
+    assert_eq!(tokenizer.balance(&router.address), 0);
+    assert_eq!(token_usdc.balance(&router.address), 50);
+    assert_eq!(token_usdc.balance(&tokenizer.address), 80);
+    assert_eq!(token_usdc.allowance(&router.address, &tokenizer.address), 0);
+    assert_eq!(tokenizer.cash_reserves(), 80);
+}
+
+#[test]
+fn test_liquidity_buffer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    assert_eq!(tokenizer.liquidity_buffer(), LiquidityBuffer::Amount(0));
+    assert_eq!(tokenizer.admin_withdrawable(&token_usdc.address), 100);
+    for buffer in [LiquidityBuffer::Bps(10_001), LiquidityBuffer::Amount(-1)] {
+        assert_eq!(
+            tokenizer.try_set_liquidity_buffer(&buffer),
+            Err(Ok(ExcellarError::InvalidLiquidityBuffer))
+        );
+    }
+
+    tokenizer.set_liquidity_buffer(&LiquidityBuffer::Bps(2_000));
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "liquidity_buffer"),).into_val(&e),
+            LiquidityBuffer::Bps(2_000).into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.admin_withdrawable(&token_usdc.address), 80);
+    assert_eq!(
+        tokenizer.try_withdraw_admin(&admin1, &admin1, &token_usdc.address, &81),
+        Err(Ok(ExcellarError::LiquidityBufferBreached))
+    );
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &80);
+    assert_eq!(token_usdc.balance(&tokenizer.address), 20);
+    assert_eq!(tokenizer.admin_withdrawable(&token_usdc.address), 0);
+
+    // The buffer grows with the NAV.
+    tokenizer.set_etf_market_value(&admin1, &80);
+    assert_eq!(tokenizer.admin_withdrawable(&token_usdc.address), 0);
+    assert_eq!(
+        tokenizer.try_withdraw_admin(&admin1, &admin1, &token_usdc.address, &1),
+        Err(Ok(ExcellarError::LiquidityBufferBreached))
+    );
+
+    tokenizer.set_liquidity_buffer(&LiquidityBuffer::Amount(15));
+    assert_eq!(tokenizer.admin_withdrawable(&token_usdc.address), 5);
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &5);
+    assert_eq!(token_usdc.balance(&tokenizer.address), 15);
+
+    // Investors can still withdraw out of the buffer.
+    tokenizer.withdraw(&user1, &token_usdc.address, &5);
+    assert_eq!(token_usdc.balance(&user1), 9);
+
+    // Fee collection has to leave the buffer in place too.
+    tokenizer.set_liquidity_buffer(&LiquidityBuffer::Amount(5));
+    tokenizer.set_management_fee(&10_000);
+    e.ledger().with_mut(|li| li.timestamp += 365 * 86_400 / 50);
+    assert_eq!(tokenizer.fees(), 3);
+    assert_eq!(tokenizer.admin_withdrawable(&token_usdc.address), 1);
+    assert_eq!(
+        tokenizer.try_collect_fees(&admin1, &admin1),
+        Err(Ok(ExcellarError::LiquidityBufferBreached))
+    );
+    tokenizer.set_liquidity_buffer(&LiquidityBuffer::Amount(3));
+    assert_eq!(tokenizer.collect_fees(&admin1, &admin1), 3);
+}
+
+#[test]
+fn test_vault_interface() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let outsider = Address::random(&e);
+    token_usdc.mint(&user1, &1_000);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.set_etf_market_value(&admin1, &100);
+
+    assert_eq!(tokenizer.asset(), token_usdc.address);
+    assert_eq!(tokenizer.total_assets(), 200);
+    assert_eq!(tokenizer.convert_to_shares(&101), 50);
+    assert_eq!(tokenizer.convert_to_assets(&51), 102);
+    assert_eq!(tokenizer.max_deposit(&outsider), 0);
+    assert_eq!(tokenizer.max_withdraw(&outsider), 0);
+
+    tokenizer.set_transaction_fees(&TransactionFees {
+        subscription_bps: 100,
+        redemption_bps: 100,
+        min_fee: 0,
+        recipient: admin1.clone(),
+    });
+    // 101 less a fee of 2 buys 49.5 XUSG, rounded down.
+    assert_eq!(tokenizer.preview_deposit(&101), 49);
+    // Minting 50 XUSG worth 100 takes 102 before the fee, and paying out 99
+    // after it burns those 50 again.
+    assert_eq!(tokenizer.preview_mint(&50), 102);
+    assert_eq!(tokenizer.preview_redeem(&99), 50);
+
+    // Minting 50 XUSG takes 100 after fees, rounded up to 102 before them.
+    let minted = tokenizer.mint(&user1, &50);
+    assert_eq!(minted.xusg, 50);
+    assert_eq!(minted.gross, 102);
+    assert_eq!(tokenizer.balance(&user1), 150);
+
+    // Paying out 99 burns the 50 XUSG worth 100 before fees.
+    let redeemed = tokenizer.redeem(&user1, &99);
+    assert_eq!(redeemed.xusg, 50);
+    assert_eq!(redeemed.net, 99);
+    assert_eq!(token_usdc.balance(&user1), 897);
+
+    // The 100 XUSG left are worth 200, more than the 100 USDC on hand.
+    assert_eq!(token_usdc.balance(&tokenizer.address), 100);
+    assert_eq!(tokenizer.max_withdraw(&user1), 99);
+    tokenizer.pause(&admin1, &Operation::Deposit);
+    assert_eq!(tokenizer.max_deposit(&user1), 0);
+}
+
+#[test]
+fn test_transaction_fees() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let recipient = Address::random(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &10_000);
+
+    assert_eq!(
+        tokenizer.try_set_transaction_fees(&TransactionFees {
+            subscription_bps: 10_001,
+            redemption_bps: 0,
+            min_fee: 0,
+            recipient: recipient.clone(),
+        }),
+        Err(Ok(ExcellarError::InvalidFeeRate))
+    );
+    tokenizer.set_transaction_fees(&TransactionFees {
+        subscription_bps: 50,
+        redemption_bps: 100,
+        min_fee: 2,
+        recipient: recipient.clone(),
+    });
+
+    // 0.5% of 1,000
+    let deposit = tokenizer.deposit(&user1, &token_usdc.address, &1_000);
+    assert_eq!(
+        deposit,
+        DepositReceipt {
+            asset: token_usdc.address.clone(),
+            gross: 1_000,
+            fee: 5,
+            net: 995,
+            value: 995,
+            xusg: 995,
+            price: PRICE_UNIT,
+        }
+    );
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (symbol_short!("deposit"), user1.clone()).into_val(&e),
+            deposit.into_val(&e),
+        ),
+    );
+    assert_eq!(token_usdc.balance(&recipient), 5);
+    assert_eq!(tokenizer.cash_reserves(), 995);
+    assert_eq!(tokenizer.price(), PRICE_UNIT);
+
+    // The minimum fee applies to small deposits.
+    assert_eq!(tokenizer.deposit(&user1, &token_usdc.address, &100).fee, 2);
+    assert_eq!(token_usdc.balance(&recipient), 7);
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &2),
+        Err(Ok(ExcellarError::FeeExceedsAmount))
+    );
+
+    // 1% of 500
+    let withdrawal = tokenizer.withdraw(&user1, &token_usdc.address, &500);
+    assert_eq!(
+        withdrawal,
+        WithdrawReceipt {
+            asset: token_usdc.address.clone(),
+            xusg: 500,
+            price: PRICE_UNIT,
+            value: 500,
+            gross: 500,
+            fee: 5,
+            net: 495,
+        }
+    );
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (symbol_short!("withdraw"), user1.clone()).into_val(&e),
+            withdrawal.into_val(&e),
+        ),
+    );
+    assert_eq!(token_usdc.balance(&recipient), 12);
+    assert_eq!(token_usdc.balance(&user1), 10_000 - 1_100 + 495);
+    assert_eq!(tokenizer.cash_reserves(), 1_093 - 500);
+    assert_eq!(tokenizer.price(), PRICE_UNIT);
+}
+
+#[test]
+fn test_initialize_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    let role_granted = |role: Role| {
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "role_granted"), role, admin1.clone()).into_val(&e),
+            ().into_val(&e),
+        )
+    };
+    assert_eq!(
+        contract_events(&e, &tokenizer.address),
+        vec![
+            &e,
+            role_granted(Role::Reporter),
+            role_granted(Role::Treasurer),
+            role_granted(Role::Pauser),
+            role_granted(Role::Compliance),
+            (
+                tokenizer.address.clone(),
+                (symbol_short!("init"),).into_val(&e),
+                InitializedEvent {
+                    admin: admin1.clone(),
+                    token_usdc: token_usdc.address.clone(),
+                    token_xusg: tokenizer.xusg_id(),
+                    price_decimals: PRICE_DECIMALS,
+                }
+                .into_val(&e),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_deposit_and_withdraw_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+
+    let before = contract_events(&e, &tokenizer.address).len();
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.withdraw(&user1, &token_usdc.address, &40);
+    assert_eq!(
+        contract_events(&e, &tokenizer.address).slice(before..),
+        vec![
+            &e,
+            (
+                tokenizer.address.clone(),
+                (symbol_short!("deposit"), user1.clone()).into_val(&e),
+                DepositReceipt {
+                    asset: token_usdc.address.clone(),
+                    gross: 100,
+                    fee: 0,
+                    net: 100,
+                    value: 100,
+                    xusg: 100,
+                    price: PRICE_UNIT,
+                }
+                .into_val(&e),
+            ),
+            (
+                tokenizer.address.clone(),
+                (symbol_short!("withdraw"), user1.clone()).into_val(&e),
+                WithdrawReceipt {
+                    asset: token_usdc.address.clone(),
+                    xusg: 40,
+                    price: PRICE_UNIT,
+                    value: 40,
+                    gross: 40,
+                    fee: 0,
+                    net: 40,
+                }
+                .into_val(&e),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_admin_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let treasury = Address::random(&e);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    tokenizer.set_etf_market_value(&admin1, &50);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "etf_market_value"), admin1.clone()).into_val(&e),
+            EtfMarketValueEvent {
+                value: 50,
+                updated_at: 1_000,
+            }
+            .into_val(&e),
+        ),
+    );
+
+    tokenizer.set_cash_reserves(&admin1, &80);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "cash_reserves"), admin1.clone()).into_val(&e),
+            CashReservesEvent {
+                previous: 100,
+                amount: 80,
+            }
+            .into_val(&e),
+        ),
+    );
+
+    tokenizer.withdraw_admin(&admin1, &treasury, &token_usdc.address, &20);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "admin_withdraw"), admin1.clone()).into_val(&e),
+            AdminWithdrawEvent {
+                to: treasury.clone(),
+                asset: token_usdc.address.clone(),
+                amount: 20,
+            }
+            .into_val(&e),
+        ),
+    );
+
+    tokenizer.set_management_fee(&100);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "management_fee"),).into_val(&e),
+            ManagementFeeEvent {
+                previous_bps: 0,
+                bps: 100,
+            }
+            .into_val(&e),
+        ),
+    );
+
+    let fees = TransactionFees {
+        subscription_bps: 50,
+        redemption_bps: 100,
+        min_fee: 0,
+        recipient: treasury.clone(),
+    };
+    tokenizer.set_transaction_fees(&fees);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "transaction_fees"),).into_val(&e),
+            fees.into_val(&e),
+        ),
+    );
+
+    // 1% a year of 130 USDC in assets.
+    e.ledger()
+        .with_mut(|li| li.timestamp = 1_000 + 365 * 24 * 60 * 60);
+    assert_eq!(tokenizer.collect_fees(&admin1, &treasury), 1);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "fees_collected"), admin1.clone()).into_val(&e),
+            FeesCollectedEvent {
+                to: treasury,
+                amount: 1,
+            }
+            .into_val(&e),
+        ),
+    );
+}
+
+#[test]
+fn test_config_and_redemption_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = Address::random(&e);
+    let user1 = Address::random(&e);
+
+    let limits = Limits {
+        max_total_assets: Some(1_000),
+        max_position: None,
+        address_daily_redemption: None,
+        global_daily_redemption: None,
+    };
+    tokenizer.set_limits(&limits);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (symbol_short!("limits"),).into_val(&e),
+            limits.into_val(&e),
+        ),
+    );
+
+    let config = OracleConfig {
+        oracle: oracle.clone(),
+        asset: Asset::Other(symbol_short!("XUSGETF")),
+    };
+    tokenizer.set_oracle(&config.oracle, &config.asset);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "oracle_set"),).into_val(&e),
+            config.into_val(&e),
+        ),
+    );
+
+    tokenizer.remove_oracle();
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "oracle_removed"),).into_val(&e),
+            ().into_val(&e),
+        ),
+    );
+
+    tokenizer.set_nav_heartbeat(&3_600);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "nav_heartbeat"),).into_val(&e),
+            3_600_u64.into_val(&e),
+        ),
+    );
+
+    let investor = Investor {
+        account: user1.clone(),
+        category: InvestorCategory::Retail,
+        expires_at: 100_000,
+    };
+    tokenizer.add_investors(&admin1, &vec![&e, investor.clone()]);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "investor_added"), admin1.clone()).into_val(&e),
+            investor.into_val(&e),
+        ),
+    );
+
+    tokenizer.set_allowlist_enabled(&false);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "allowlist_enabled"),).into_val(&e),
+            false.into_val(&e),
+        ),
+    );
+
+    tokenizer.set_redemption_price_mode(&RedemptionPriceMode::AtRequest);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "redemption_price_mode"),).into_val(&e),
+            RedemptionPriceMode::AtRequest.into_val(&e),
+        ),
+    );
+
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    let request_id = tokenizer.request_redeem(&user1, &token_usdc.address, &40);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (
+                Symbol::new(&e, "redeem_requested"),
+                user1.clone(),
+                request_id,
+            )
+                .into_val(&e),
+            RedemptionRequest {
+                owner: user1.clone(),
+                asset: token_usdc.address.clone(),
+                xusg_amount: 40,
+                locked_price: Some(PRICE_UNIT),
+                requested_at: 1_000,
+            }
+            .into_val(&e),
+        ),
+    );
+
+    tokenizer.cancel_redeem(&user1, &request_id);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (
+                Symbol::new(&e, "redeem_cancelled"),
+                user1.clone(),
+                request_id,
+            )
+                .into_val(&e),
+            40_i128.into_val(&e),
+        ),
+    );
+
+    tokenizer.remove_investors(&admin1, &vec![&e, user1.clone()]);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "investor_removed"), admin1.clone()).into_val(&e),
+            user1.into_val(&e),
+        ),
+    );
+}
+
+#[test]
+fn test_upgrade_keeps_state() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    assert_eq!(tokenizer.schema_version(), SCHEMA_VERSION);
+
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &1_000);
+    tokenizer.deposit(&user1, &token_usdc.address, &1_000);
+    tokenizer.set_etf_market_value(&admin1, &500);
+    tokenizer.set_cash_reserves(&admin1, &700);
+    let price = tokenizer.price();
+
+    let new_wasm_hash = install_tokenizer_wasm(&e);
+    tokenizer.upgrade(&new_wasm_hash);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (symbol_short!("upgraded"),).into_val(&e),
+            new_wasm_hash.into_val(&e),
+        ),
+    );
+
+    // Already on the current layout, so there is nothing to convert.
+    assert_eq!(tokenizer.migrate(), SCHEMA_VERSION);
+    assert_eq!(tokenizer.schema_version(), SCHEMA_VERSION);
+
+    assert_eq!(tokenizer.balance(&user1), 1_000);
+    assert_eq!(tokenizer.total(), 1_000);
+    assert_eq!(tokenizer.etf_market_value(), 500);
+    assert_eq!(tokenizer.cash_reserves(), 700);
+    assert_eq!(tokenizer.price(), price);
+    assert!(tokenizer.has_role(&Role::Reporter, &admin1));
+    assert!(tokenizer.is_allowlisted(&user1));
+
+    let withdrawal = tokenizer.withdraw(&user1, &token_usdc.address, &100);
+    assert_eq!(withdrawal.net, 120);
+    assert_eq!(token_usdc.balance(&user1), 120);
+}
+
+#[test]
+fn test_migrate_from_v1() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let token_xusg = create_token_contract(&e, &admin1);
+    let tokenizer = ExcellarTokenizerClient::new(
+        &e,
+        &e.register_contract(None, crate::contract::ExcellarTokenizer {}),
+    );
+
+    // The layout written by the first release.
+    e.as_contract(&tokenizer.address, || {
+        let storage = e.storage().persistent();
+        storage.set(&DataKey::TokenUSDC, &token_usdc.address);
+        storage.set(&DataKey::TokenXUSG, &token_xusg.address);
+        storage.set(&DataKey::TotalXUSG, &100_i128);
+        storage.set(&DataKey::MarketETFPrice, &150_i128);
+        storage.set(&DataKey::ReservesCash, &60_i128);
+        storage.set(&DataKey::Admin, &admin1);
+        storage.set(&DataKey::Fees, &10_i128);
+    });
+    token_usdc.mint(&tokenizer.address, &40);
+    assert_eq!(tokenizer.schema_version(), 1);
+
+    assert_eq!(tokenizer.migrate(), SCHEMA_VERSION);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (symbol_short!("migrated"),).into_val(&e),
+            (1_u32, SCHEMA_VERSION).into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.schema_version(), SCHEMA_VERSION);
+    e.as_contract(&tokenizer.address, || {
+        assert!(!e.storage().persistent().has(&DataKey::TokenUSDC));
+        assert!(e.storage().instance().has(&DataKey::TokenUSDC));
+    });
+    assert_eq!(tokenizer.price_decimals(), V1_MIGRATED_PRICE_DECIMALS);
+    assert_eq!(
+        tokenizer.price(),
+        2 * 10_i128.pow(V1_MIGRATED_PRICE_DECIMALS)
+    );
+    assert_eq!(tokenizer.fees(), 10);
+    assert_eq!(tokenizer.total(), 100);
+    assert_eq!(tokenizer.cash_reserves(), 60);
+    assert!(tokenizer.has_role(&Role::Treasurer, &admin1));
+    assert!(!tokenizer.allowlist_enabled());
+    assert_eq!(
+        tokenizer.redemption_price_mode(),
+        RedemptionPriceMode::AtFulfilment
+    );
+    assert_eq!(
+        tokenizer.collateral_assets(),
+        vec![&e, token_usdc.address.clone()]
+    );
+    assert_eq!(tokenizer.asset_reserves(&token_usdc.address), 40);
+
+    // Running it again is a no-op.
+    assert_eq!(tokenizer.migrate(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_admin_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let admin2 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    assert_eq!(tokenizer.admin(), admin1);
+    assert_eq!(tokenizer.pending_admin(), None);
+    assert_eq!(
+        tokenizer.try_accept_admin(),
+        Err(Ok(GovernanceError::NoPendingAdmin))
+    );
+
+    let pending = PendingAdmin {
+        admin: admin2.clone(),
+        expires_at: Some(2_000),
+    };
+    tokenizer.propose_admin(&admin2, &Some(2_000));
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            admin1.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    Symbol::new(&e, "propose_admin"),
+                    (&admin2, Some(2_000_u64)).into_val(&e),
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "admin_proposed"), admin1.clone()).into_val(&e),
+            pending.clone().into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.pending_admin(), Some(pending));
+    assert_eq!(tokenizer.admin(), admin1);
+
+    tokenizer.accept_admin();
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            admin2.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    tokenizer.address.clone(),
+                    Symbol::new(&e, "accept_admin"),
+                    ().into_val(&e),
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "admin_changed"), admin1.clone()).into_val(&e),
+            admin2.clone().into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.admin(), admin2);
+    assert_eq!(tokenizer.pending_admin(), None);
+
+    // The roles the previous admin held move with the handover.
+    for role in [
+        Role::Reporter,
+        Role::Treasurer,
+        Role::Pauser,
+        Role::Compliance,
+    ] {
+        assert!(tokenizer.has_role(&role, &admin2));
+        assert!(!tokenizer.has_role(&role, &admin1));
+    }
+    assert!(!tokenizer.has_role(&Role::Auditor, &admin2));
+
+    // The new admin signs for admin-only calls from now on.
+    tokenizer.set_nav_heartbeat(&60);
+    assert_eq!(e.auths()[0].0, admin2);
+}
+
+#[test]
+fn test_admin_proposal_expiry() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let admin2 = Address::random(&e);
+    let admin3 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    tokenizer.propose_admin(&admin2, &Some(2_000));
+    e.ledger().with_mut(|li| li.timestamp = 2_000);
+    assert_eq!(
+        tokenizer.try_accept_admin(),
+        Err(Ok(GovernanceError::AdminProposalExpired))
+    );
+    assert_eq!(tokenizer.admin(), admin1);
+
+    // A new proposal replaces the expired one; without an expiry it stays
+    // open until accepted.
+    tokenizer.propose_admin(&admin3, &None);
+    e.ledger().with_mut(|li| li.timestamp = 1_000_000);
+    tokenizer.accept_admin();
+    assert_eq!(tokenizer.admin(), admin3);
+}
+
+#[test]
+fn test_nav_history() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| {
+        li.timestamp = 1_000;
+        li.sequence_number = 10;
+    });
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    assert_eq!(tokenizer.nav_history_len(), 0);
+    assert_eq!(
+        tokenizer.try_price_at(&1_000),
+        Err(Ok(ExcellarError::NavNotFound))
+    );
+
+    tokenizer.set_etf_market_value(&admin1, &100);
+    e.ledger().with_mut(|li| {
+        li.timestamp = 2_000;
+        li.sequence_number = 20;
+    });
+    tokenizer.set_cash_reserves(&admin1, &200);
+    e.ledger().with_mut(|li| {
+        li.timestamp = 3_000;
+        li.sequence_number = 30;
+    });
+    tokenizer.set_etf_market_value(&admin1, &400);
+
+    let first = NavSnapshot {
+        ledger: 10,
+        timestamp: 1_000,
+        etf_market_value: 100,
+        cash_reserves: 100,
+        fees: 0,
+        total_xusg: 100,
+        price: 2 * PRICE_UNIT,
+    };
+    let second = NavSnapshot {
+        ledger: 20,
+        timestamp: 2_000,
+        etf_market_value: 100,
+        cash_reserves: 200,
+        fees: 0,
+        total_xusg: 100,
+        price: 3 * PRICE_UNIT,
+    };
+    let third = NavSnapshot {
+        ledger: 30,
+        timestamp: 3_000,
+        etf_market_value: 400,
+        cash_reserves: 200,
+        fees: 0,
+        total_xusg: 100,
+        price: 6 * PRICE_UNIT,
+    };
+    assert_eq!(tokenizer.nav_history_len(), 3);
+    assert_eq!(
+        tokenizer.nav_history(&0, &10),
+        vec![&e, first.clone(), second.clone(), third.clone()]
+    );
+    assert_eq!(tokenizer.nav_history(&1, &1), vec![&e, second.clone()]);
+    assert_eq!(tokenizer.nav_history(&3, &10), vec![&e]);
+
+    assert_eq!(
+        tokenizer.try_price_at(&999),
+        Err(Ok(ExcellarError::NavNotFound))
+    );
+    assert_eq!(tokenizer.price_at(&1_000), 2 * PRICE_UNIT);
+    assert_eq!(tokenizer.price_at(&2_999), 3 * PRICE_UNIT);
+    assert_eq!(tokenizer.price_at(&u64::MAX), 6 * PRICE_UNIT);
+    assert_eq!(tokenizer.nav_at(&2_500), second);
+}
+
+#[test]
+fn test_reserve_attestations() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let auditor = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    let report_hash = BytesN::from_array(&e, &[7; 32]);
+    let uri = String::from_str(&e, "ipfs://report-1");
+    assert_eq!(
+        tokenizer.try_reserve_ratio(),
+        Err(Ok(ExcellarError::NoAttestation))
+    );
+    // The admin has to register auditors; it can't attest itself.
+    assert_eq!(
+        tokenizer.try_attest(&admin1, &report_hash, &100, &900, &uri),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+
+    tokenizer.grant_role(&Role::Auditor, &auditor);
+    assert_eq!(
+        tokenizer.try_attest(&auditor, &report_hash, &-1, &900, &uri),
+        Err(Ok(ExcellarError::NegativeAmount))
+    );
+    assert_eq!(
+        tokenizer.try_attest(&auditor, &report_hash, &100, &1_001, &uri),
+        Err(Ok(ExcellarError::InvalidAttestation))
+    );
+
+    let ratio = tokenizer.attest(&auditor, &report_hash, &100, &900, &uri);
+    assert_eq!(
+        ratio,
+        ReserveRatio {
+            attested: 100,
+            liabilities: 100,
+            ratio_bps: 10_000,
+            floor_bps: 10_000,
+            below_floor: false,
+        }
+    );
+    let first = Attestation {
+        attester: auditor.clone(),
+        report_hash: report_hash.clone(),
+        custody_total: 100,
+        reported_at: 900,
+        uri: uri.clone(),
+        recorded_at: 1_000,
+    };
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "attestation"), auditor.clone()).into_val(&e),
+            first.clone().into_val(&e),
+        ),
+    );
+
+    // Reports arrive in order.
+    assert_eq!(
+        tokenizer.try_attest(&auditor, &report_hash, &100, &899, &uri),
+        Err(Ok(ExcellarError::InvalidAttestation))
+    );
+
+    let uri = String::from_str(&e, "ipfs://report-2");
+    let ratio = tokenizer.attest(&auditor, &report_hash, &95, &1_000, &uri);
+    assert_eq!(ratio.ratio_bps, 9_500);
+    assert!(ratio.below_floor);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "reserve_shortfall"),).into_val(&e),
+            ratio.into_val(&e),
+        ),
+    );
+
+    tokenizer.set_reserve_ratio_floor(&9_000);
+    assert_eq!(tokenizer.reserve_ratio_floor(), 9_000);
+    assert!(!tokenizer.reserve_ratio().below_floor);
+
+    // The ratio follows the liabilities between attestations.
+    tokenizer.set_etf_market_value(&admin1, &100);
+    let ratio = tokenizer.reserve_ratio();
+    assert_eq!((ratio.liabilities, ratio.ratio_bps), (200, 4_750));
+    assert!(ratio.below_floor);
+
+    assert_eq!(tokenizer.attestation_count(), 2);
+    assert_eq!(tokenizer.attestations(&0, &1), vec![&e, first]);
+    assert_eq!(tokenizer.latest_attestation().uri, uri);
+}
+
+#[test]
+fn test_signed_nav() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 900);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+
+    let custodians = [1, 2, 3, 4].map(|seed| SigningKey::from_bytes(&[seed; 32]));
+    let public_keys = custodians
+        .clone()
+        .map(|key| BytesN::from_array(&e, &key.verifying_key().to_bytes()));
+    for (keys, threshold) in [
+        (vec![&e, public_keys[0].clone()], 0),
+        (vec![&e, public_keys[0].clone()], 2),
+        (vec![&e, public_keys[0].clone(), public_keys[0].clone()], 1),
+    ] {
+        assert_eq!(
+            tokenizer.try_set_nav_signers(&NavSigners { keys, threshold }),
+            Err(Ok(ExcellarError::InvalidSignerConfig))
+        );
+    }
+    let signers = NavSigners {
+        keys: vec![
+            &e,
+            public_keys[0].clone(),
+            public_keys[1].clone(),
+            public_keys[2].clone(),
+        ],
+        threshold: 2,
+    };
+    tokenizer.set_nav_signers(&signers);
+    assert_eq!(tokenizer.nav_signers(), Some(signers));
+    tokenizer.set_nav_heartbeat(&60);
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    // From here on nobody signs the invocations: any relayer can submit.
+    e.set_auths(&[]);
+    let payload = NavPayload {
+        etf_market_value: 100,
+        cash_reserves: 100,
+        fees: 0,
+        timestamp: 990,
+        nonce: 1,
+    };
+    let [first, second, third, outsider] = custodians
+        .clone()
+        .map(|key| sign_nav(&e, &tokenizer, &key, &payload));
+    assert_eq!(
+        tokenizer.try_submit_nav(&payload, &vec![&e, first.clone()]),
+        Err(Ok(ExcellarError::InsufficientSignatures))
+    );
+    assert_eq!(
+        tokenizer.try_submit_nav(&payload, &vec![&e, first.clone(), first.clone()]),
+        Err(Ok(ExcellarError::DuplicateSigner))
+    );
+    assert_eq!(
+        tokenizer.try_submit_nav(&payload, &vec![&e, first.clone(), outsider]),
+        Err(Ok(ExcellarError::UnknownSigner))
+    );
+
+    tokenizer.submit_nav(&payload, &vec![&e, third, first.clone()]);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "signed_nav"), 1_u64).into_val(&e),
+            payload.clone().into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.etf_market_value(), 100);
+    assert_eq!(tokenizer.cash_reserves(), 100);
+    assert_eq!(tokenizer.price(), 2 * PRICE_UNIT);
+    assert_eq!(tokenizer.nav_updated_at(), 990);
+    assert_eq!(tokenizer.nav_nonce(), 1);
+
+    assert_eq!(
+        tokenizer.try_submit_nav(&payload, &vec![&e, first.clone(), second]),
+        Err(Ok(ExcellarError::NavNonceUsed))
+    );
+
+    let stale = |timestamp| {
+        let payload = NavPayload {
+            timestamp,
+            nonce: 2,
+            ..payload.clone()
+        };
+        let signatures = vec![
+            &e,
+            sign_nav(&e, &tokenizer, &custodians[0], &payload),
+            sign_nav(&e, &tokenizer, &custodians[1], &payload),
+        ];
+        tokenizer.try_submit_nav(&payload, &signatures)
+    };
+    // Older than the current NAV, from the future, and past the heartbeat.
+    assert_eq!(stale(989), Err(Ok(ExcellarError::InvalidNavTimestamp)));
+    assert_eq!(stale(1_001), Err(Ok(ExcellarError::InvalidNavTimestamp)));
+    e.ledger().with_mut(|li| li.timestamp = 1_100);
+    assert_eq!(stale(1_000), Err(Ok(ExcellarError::InvalidNavTimestamp)));
+    assert_eq!(stale(1_050), Ok(Ok(())));
+    assert_eq!(tokenizer.nav_nonce(), 2);
+}
+
+#[test]
+fn test_timelock() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let guardian = Address::random(&e);
+    let treasurer = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &100);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.grant_role(&Role::Guardian, &guardian);
+    tokenizer.grant_role(&Role::Treasurer, &treasurer);
+
+    assert_eq!(
+        tokenizer.try_set_timelock_delay(&(MAX_TIMELOCK_DELAY + 1)),
+        Err(Ok(GovernanceError::InvalidTimelockDelay))
+    );
+    tokenizer.set_timelock_delay(&3_600);
+    assert_eq!(tokenizer.timelock_delay(), 3_600);
+
+    // Sensitive calls can no longer be made directly.
+    assert_eq!(
+        tokenizer.try_set_management_fee(&100),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_withdraw_admin(&admin1, &admin1, &token_usdc.address, &40),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_propose_admin(&user1, &None),
+        Err(Ok(GovernanceError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_timelock_delay(&0),
+        Err(Ok(GovernanceError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_upgrade(&BytesN::from_array(&e, &[0; 32])),
+        Err(Ok(GovernanceError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_collect_fees(&admin1, &admin1),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_grant_role(&Role::Reporter, &user1),
+        Err(Ok(GovernanceError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_revoke_role(&Role::Guardian, &guardian),
+        Err(Ok(GovernanceError::TimelockRequired))
+    );
+
+    let fee_call = TimelockCall::SetManagementFee(100);
+    let fee_hash = tokenizer.schedule(&fee_call, &BytesN::from_array(&e, &[0; 32]));
+    let scheduled = ScheduledOperation {
+        call: fee_call.clone(),
+        scheduled_at: 1_000,
+        ready_at: 4_600,
+    };
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "op_scheduled"), fee_hash.clone()).into_val(&e),
+            scheduled.clone().into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.scheduled_operation(&fee_hash), scheduled);
+    assert_eq!(
+        tokenizer.try_schedule(&fee_call, &BytesN::from_array(&e, &[0; 32])),
+        Err(Ok(GovernanceError::OperationAlreadyScheduled))
+    );
+
+    // Only the treasurer named in a withdrawal can schedule it.
+    let withdrawal = |treasurer: &Address| {
+        TimelockCall::WithdrawAdmin(AdminWithdrawal {
+            treasurer: treasurer.clone(),
+            to: admin1.clone(),
+            asset: token_usdc.address.clone(),
+            amount: 40,
+        })
+    };
+    assert_eq!(
+        tokenizer.try_schedule(&withdrawal(&user1), &BytesN::from_array(&e, &[1; 32])),
+        Err(Ok(GovernanceError::Unauthorized))
+    );
+    let withdraw_hash = tokenizer.schedule(&withdrawal(&admin1), &BytesN::from_array(&e, &[1; 32]));
+    assert_eq!(
+        tokenizer.timelock_queue(),
+        vec![&e, fee_hash.clone(), withdraw_hash.clone()]
+    );
+
+    // The guardian, not the admin, can cancel.
+    assert_eq!(
+        tokenizer.try_cancel(&admin1, &withdraw_hash),
+        Err(Ok(GovernanceError::Unauthorized))
+    );
+    tokenizer.cancel(&guardian, &withdraw_hash);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "op_cancelled"), guardian.clone()).into_val(&e),
+            withdraw_hash.clone().into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.timelock_queue(), vec![&e, fee_hash.clone()]);
+    assert_eq!(
+        tokenizer.try_execute(&withdraw_hash),
+        Err(Ok(GovernanceError::OperationNotFound.into()))
+    );
+    assert_eq!(token_usdc.balance(&tokenizer.address), 100);
+
+    // Other roles can still be revoked at once, and a withdrawal fails if
+    // its treasurer lost the role while it was queued.
+    let revoked_hash =
+        tokenizer.schedule(&withdrawal(&treasurer), &BytesN::from_array(&e, &[2; 32]));
+    tokenizer.revoke_role(&Role::Treasurer, &treasurer);
+
+    assert_eq!(
+        tokenizer.try_execute(&fee_hash),
+        Err(Ok(GovernanceError::OperationNotReady.into()))
+    );
+    e.ledger().with_mut(|li| li.timestamp = 4_600);
+    assert_eq!(
+        tokenizer.try_execute(&revoked_hash),
+        Err(Ok(ExcellarError::Unauthorized.into()))
+    );
+    assert_eq!(token_usdc.balance(&tokenizer.address), 100);
+    tokenizer.cancel(&guardian, &revoked_hash);
+    tokenizer.execute(&fee_hash);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "op_executed"), fee_hash.clone()).into_val(&e),
+            fee_call.into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.management_fee(), 100);
+    assert_eq!(tokenizer.timelock_queue(), vec![&e]);
+
+    // Turning the timelock off goes through the timelock as well.
+    let hash = tokenizer.schedule(
+        &TimelockCall::SetTimelockDelay(0),
+        &BytesN::from_array(&e, &[0; 32]),
+    );
+    e.ledger().with_mut(|li| li.timestamp = 8_200);
+    tokenizer.execute(&hash);
+    assert_eq!(tokenizer.timelock_delay(), 0);
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_usdc.address, &40);
+    assert_eq!(token_usdc.balance(&tokenizer.address), 60);
+}
+
+#[test]
+fn test_rogue_guardian_cannot_cancel_revocation() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let guardian = Address::random(&e);
+    let rogue = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    tokenizer.grant_role(&Role::Guardian, &guardian);
+    tokenizer.grant_role(&Role::Guardian, &rogue);
+    tokenizer.set_timelock_delay(&3_600);
+
+    let revoke = |account: &Address| {
+        TimelockCall::RevokeRole(RoleChange {
+            role: Role::Guardian,
+            account: account.clone(),
+        })
+    };
+    let hash = tokenizer.schedule(&revoke(&rogue), &BytesN::from_array(&e, &[0; 32]));
+
+    // Neither the rogue guardian nor another guardian can stop it.
+    for account in [&rogue, &guardian] {
+        assert_eq!(
+            tokenizer.try_cancel(account, &hash),
+            Err(Ok(GovernanceError::OperationNotCancellable))
+        );
+    }
+    assert_eq!(tokenizer.timelock_queue(), vec![&e, hash.clone()]);
+
+    // Other operations can still be cancelled.
+    let grant_hash = tokenizer.schedule(
+        &TimelockCall::GrantRole(RoleChange {
+            role: Role::Guardian,
+            account: rogue.clone(),
+        }),
+        &BytesN::from_array(&e, &[1; 32]),
+    );
+    tokenizer.cancel(&guardian, &grant_hash);
+
+    e.ledger().with_mut(|li| li.timestamp = 4_600);
+    tokenizer.execute(&hash);
+    assert!(!tokenizer.has_role(&Role::Guardian, &rogue));
+    assert!(tokenizer.has_role(&Role::Guardian, &guardian));
+    assert_eq!(
+        tokenizer.try_cancel(&rogue, &grant_hash),
+        Err(Ok(GovernanceError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_timelocked_configuration() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let token_eurc = create_token_contract(&e, &admin1);
+    let token_usdt = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = create_oracle_contract(&e);
+    let eurc = Collateral {
+        decimals: 7,
+        rate: ConversionRate::Fixed(11 * USDC_UNIT / 10),
+    };
+    tokenizer.set_collateral(&token_eurc.address, &eurc);
+    tokenizer.set_timelock_delay(&3_600);
+
+    let usdt = Collateral {
+        decimals: 7,
+        rate: ConversionRate::Fixed(USDC_UNIT),
+    };
+    let config = OracleConfig {
+        oracle: oracle.address.clone(),
+        asset: Asset::Other(Symbol::new(&e, "XUSG")),
+    };
+    let signers = NavSigners {
+        keys: vec![&e, BytesN::from_array(&e, &[1; 32])],
+        threshold: 1,
+    };
+    let limits = Limits {
+        max_total_assets: Some(1_000),
+        max_position: None,
+        address_daily_redemption: None,
+        global_daily_redemption: Some(500),
+    };
+    let buffer = LiquidityBuffer::Bps(1_000);
+
+    // None of the settings can be changed directly any more.
+    assert_eq!(
+        tokenizer.try_set_collateral(&token_usdt.address, &usdt),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_remove_collateral(&token_eurc.address),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_collateral_heartbeat(&600),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_oracle(&config.oracle, &config.asset),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_remove_oracle(),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_oracle_max_deviation(&500),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_nav_heartbeat(&600),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_nav_signers(&signers),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_limits(&limits),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_liquidity_buffer(&buffer),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(
+        tokenizer.try_set_redemption_price_mode(&RedemptionPriceMode::AtRequest),
+        Err(Ok(ExcellarError::TimelockRequired))
+    );
+    assert_eq!(tokenizer.collateral(&token_usdt.address), None);
+    assert_eq!(tokenizer.collateral(&token_eurc.address), Some(eurc));
+    assert_eq!(tokenizer.oracle(), None);
+    assert_eq!(tokenizer.nav_signers(), None);
+    assert_eq!(tokenizer.liquidity_buffer(), LiquidityBuffer::Amount(0));
+
+    // Each goes through once its delay has passed.
+    let calls = [
+        TimelockCall::SetCollateral(CollateralChange {
+            asset: token_usdt.address.clone(),
+            collateral: usdt.clone(),
+        }),
+        TimelockCall::RemoveCollateral(token_eurc.address.clone()),
+        TimelockCall::SetCollateralHeartbeat(600),
+        TimelockCall::SetOracle(config.clone()),
+        TimelockCall::SetOracleMaxDeviation(500),
+        TimelockCall::SetNavHeartbeat(600),
+        TimelockCall::SetNavSigners(signers.clone()),
+        TimelockCall::SetLimits(limits.clone()),
+        TimelockCall::SetLiquidityBuffer(buffer.clone()),
+        TimelockCall::SetRedemptionPriceMode(RedemptionPriceMode::AtRequest),
+        TimelockCall::RemoveOracle,
+    ];
+    let mut hashes = std::vec::Vec::new();
+    for (index, call) in calls.iter().enumerate() {
+        hashes.push(tokenizer.schedule(call, &BytesN::from_array(&e, &[index as u8; 32])));
+    }
+    assert_eq!(
+        tokenizer.try_execute(&hashes[0]),
+        Err(Ok(GovernanceError::OperationNotReady.into()))
+    );
+
+    e.ledger().with_mut(|li| li.timestamp = 4_600);
+    for hash in &hashes[..hashes.len() - 1] {
+        tokenizer.execute(hash);
+    }
+    assert_eq!(tokenizer.collateral(&token_usdt.address), Some(usdt));
+    assert_eq!(tokenizer.collateral(&token_eurc.address), None);
+    assert_eq!(tokenizer.collateral_heartbeat(), 600);
+    assert_eq!(tokenizer.oracle(), Some(config));
+    assert_eq!(tokenizer.oracle_max_deviation(), 500);
+    assert_eq!(tokenizer.nav_heartbeat(), 600);
+    assert_eq!(tokenizer.nav_signers(), Some(signers));
+    assert_eq!(tokenizer.limits(), limits);
+    assert_eq!(tokenizer.liquidity_buffer(), buffer);
+    assert_eq!(
+        tokenizer.redemption_price_mode(),
+        RedemptionPriceMode::AtRequest
+    );
+
+    tokenizer.execute(&hashes[hashes.len() - 1]);
+    assert_eq!(tokenizer.oracle(), None);
+    assert_eq!(tokenizer.timelock_queue(), vec![&e]);
+}
+
+#[test]
+fn test_initialization_errors() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let user1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer = ExcellarTokenizerClient::new(
+        &e,
+        &e.register_contract(None, crate::contract::ExcellarTokenizer {}),
+    );
+
+    assert_eq!(
+        tokenizer.try_price(),
+        Err(Ok(ExcellarError::NotInitialized))
+    );
+    assert_eq!(
+        tokenizer.try_cash_reserves(),
+        Err(Ok(ExcellarError::NotInitialized))
+    );
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::NotInitialized))
+    );
+    assert_eq!(
+        tokenizer.try_set_etf_market_value(&admin1, &10),
+        Err(Ok(ExcellarError::NotInitialized))
+    );
+    assert_eq!(
+        tokenizer.try_set_nav_heartbeat(&60),
+        Err(Ok(ExcellarError::NotInitialized))
+    );
+
+    let token_wasm_hash = install_token_wasm(&e);
+    tokenizer.initialize(
+        &token_wasm_hash,
+        &token_usdc.address,
+        &admin1,
+        &PRICE_DECIMALS,
+        &xusg_share(&e),
+    );
+    assert_eq!(
+        tokenizer.try_initialize(
+            &token_wasm_hash,
+            &token_usdc.address,
+            &user1,
+            &PRICE_DECIMALS,
+            &xusg_share(&e)
+        ),
+        Err(Ok(ExcellarError::AlreadyInitialized))
+    );
+    assert_eq!(tokenizer.admin(), admin1);
+}
+
+#[test]
+fn test_reporter_errors() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let user1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+
+    assert_eq!(
+        tokenizer.try_set_etf_market_value(&user1, &10),
+        Err(Ok(ExcellarError::Unauthorized))
+    );
+    assert_eq!(
+        tokenizer.try_set_etf_market_value(&admin1, &0),
+        Err(Ok(ExcellarError::AmountMustBePositive))
+    );
+    assert_eq!(
+        tokenizer.try_set_cash_reserves(&admin1, &-1),
+        Err(Ok(ExcellarError::NegativeAmount))
+    );
+
+    // A full year at a 100% fee leaves nothing once the cash is gone.
+    let investor = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&investor, &1_000);
+    tokenizer.deposit(&investor, &token_usdc.address, &1_000);
+    assert_eq!(
+        tokenizer.try_set_etf_market_value(&admin1, &(i128::MAX / 2)),
+        Err(Ok(ExcellarError::ArithmeticOverflow))
+    );
+    tokenizer.set_etf_market_value(&admin1, &1);
+    assert_eq!(
+        tokenizer.try_set_cash_reserves(&admin1, &i128::MAX),
+        Err(Ok(ExcellarError::ArithmeticOverflow))
+    );
+    tokenizer.set_management_fee(&10_000);
+    e.ledger().with_mut(|li| li.timestamp += 365 * 24 * 60 * 60);
+    assert_eq!(
+        tokenizer.try_set_cash_reserves(&admin1, &0),
+        Err(Ok(ExcellarError::PriceNotPositive))
+    );
+    assert_eq!(tokenizer.cash_reserves(), 1_000);
+
+    // The fees have eaten the whole fund, which leaves XUSG without a price
+    // rather than back at par.
+    assert_eq!(tokenizer.total_assets(), 0);
+    assert_eq!(
+        tokenizer.try_price(),
+        Err(Ok(ExcellarError::PriceNotPositive))
+    );
+    token_usdc.mint(&investor, &1_000);
+    assert_eq!(
+        tokenizer.try_deposit(&investor, &token_usdc.address, &1_000),
+        Err(Ok(ExcellarError::PriceNotPositive))
+    );
+    assert_eq!(
+        tokenizer.try_withdraw(&investor, &token_usdc.address, &100),
+        Err(Ok(ExcellarError::PriceNotPositive))
+    );
+}
+
+#[test]
+fn test_storage_outlives_ttl() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    let user2 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &1_000);
+    token_usdc.mint(&user2, &1_000);
+    tokenizer.deposit(&user1, &token_usdc.address, &100);
+    tokenizer.request_redeem(&user1, &token_usdc.address, &10);
+
+    // Ninety days of ledgers, well past every TTL set so far. A keeper calls
+    // in daily, which keeps the instance alive, and extends the records that
+    // nobody else touches in the meantime.
+    let records = vec![
+        &e,
+        RecordKey::Investor(user1.clone()),
+        RecordKey::Investor(user2.clone()),
+        RecordKey::Redemption(0),
+    ];
+    for _ in 0..90 {
+        e.ledger()
+            .with_mut(|li| li.sequence_number += DAY_IN_LEDGERS);
+        tokenizer.extend_ttl(&records);
+
+        // USDC is kept alive by its own traffic.
+        for account in [&user1, &user2, &tokenizer.address] {
+            token_usdc.balance(account);
+        }
+    }
+
+    assert_eq!(tokenizer.admin(), admin1);
+    assert_eq!(tokenizer.cash_reserves(), 100);
+    assert_eq!(tokenizer.redemption(&0).xusg_amount, 10);
+    assert!(tokenizer.has_role(&Role::Treasurer, &admin1));
+    assert!(tokenizer.is_allowlisted(&user2));
+
+    tokenizer.deposit(&user2, &token_usdc.address, &100);
+    tokenizer.withdraw(&user1, &token_usdc.address, &90);
+    assert_eq!(tokenizer.fulfil_redemptions(&1), 1);
+    assert_eq!(tokenizer.total(), 100);
+}
+
+#[test]
+fn test_stale_collateral_price_rejected() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 10_000);
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let token_usdt = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = create_oracle_contract(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdt.mint(&user1, &1_000);
+    tokenizer.set_collateral(
+        &token_usdt.address,
+        &Collateral {
+            decimals: 7,
+            rate: ConversionRate::Oracle(OracleConfig {
+                oracle: oracle.address.clone(),
+                asset: Asset::Stellar(token_usdt.address.clone()),
+            }),
+        },
+    );
+    // Collateral prices have their own heartbeat, separate from the NAV's.
+    tokenizer.set_nav_heartbeat(&60_000);
+    tokenizer.set_collateral_heartbeat(&600);
+    assert_last_event(
+        &e,
+        (
+            tokenizer.address.clone(),
+            (Symbol::new(&e, "collateral_heartbeat"),).into_val(&e),
+            600_u64.into_val(&e),
+        ),
+    );
+    assert_eq!(tokenizer.collateral_heartbeat(), 600);
+
+    oracle.set_price(&ORACLE_UNIT, &9_000);
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdt.address, &1_000),
+        Err(Ok(ExcellarError::StalePrice))
+    );
+
+    oracle.set_price(&ORACLE_UNIT, &9_500);
+    assert_eq!(
+        tokenizer.deposit(&user1, &token_usdt.address, &1_000).value,
+        1_000
+    );
+
+    oracle.set_decimals(&39);
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_usdt.address, &1_000),
+        Err(Ok(ExcellarError::ArithmeticOverflow))
+    );
+}
+
+#[test]
+fn test_collateral_revalued() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let token_usdt = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = create_oracle_contract(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_usdc.mint(&user1, &2_000);
+    token_usdt.mint(&user1, &1_000);
+    tokenizer.set_collateral(
+        &token_usdt.address,
+        &Collateral {
+            decimals: 7,
+            rate: ConversionRate::Oracle(OracleConfig {
+                oracle: oracle.address.clone(),
+                asset: Asset::Stellar(token_usdt.address.clone()),
+            }),
+        },
+    );
+
+    oracle.set_price(&ORACLE_UNIT, &0);
+    tokenizer.deposit(&user1, &token_usdt.address, &1_000);
+    assert_eq!(tokenizer.cash_reserves(), 1_000);
+
+    // The USDT held is counted at its new price before the next trade.
+    oracle.set_price(&(102 * ORACLE_UNIT / 100), &0);
+    assert_eq!(
+        tokenizer.deposit(&user1, &token_usdc.address, &1_020).xusg,
+        1_000
+    );
+    assert_eq!(tokenizer.cash_reserves(), 2_040);
+    assert_eq!(tokenizer.price(), 102 * PRICE_UNIT / 100);
+
+    oracle.set_price(&(101 * ORACLE_UNIT / 100), &0);
+    tokenizer.withdraw(&user1, &token_usdc.address, &10);
+    assert!(contract_events(&e, &tokenizer.address).contains((
+        tokenizer.address.clone(),
+        (
+            Symbol::new(&e, "collateral_revalued"),
+            token_usdt.address.clone()
+        )
+            .into_val(&e),
+        CashReservesEvent {
+            previous: 2_040,
+            amount: 2_030,
+        }
+        .into_val(&e),
+    )));
+}
+
+#[test]
+fn test_multi_collateral() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin1 = Address::random(&e);
+    let token_usdc = create_token_contract(&e, &admin1);
+    let token_eurc = create_token_contract(&e, &admin1);
+    let token_usdt = create_token_contract(&e, &admin1);
+    let tokenizer =
+        create_tokenizer_contract(&e, &install_token_wasm(&e), &token_usdc.address, &admin1);
+    let oracle = create_oracle_contract(&e);
+    let user1 = create_investor(&e, &tokenizer, &admin1);
+    token_eurc.mint(&user1, &1_000);
+    token_usdt.mint(&user1, &1_000);
+
+    assert_eq!(
+        tokenizer.collateral_assets(),
+        vec![&e, token_usdc.address.clone()]
+    );
+    assert_eq!(
+        tokenizer.try_deposit(&user1, &token_eurc.address, &1_000),
+        Err(Ok(ExcellarError::UnsupportedCollateral))
+    );
+    assert_eq!(
+        tokenizer.try_set_collateral(
+            &token_usdc.address,
+            &Collateral {
+                decimals: 7,
+                rate: ConversionRate::Fixed(2 * USDC_UNIT),
+            }
+        ),
+        Err(Ok(ExcellarError::InvalidCollateral))
+    );
+    assert_eq!(
+        tokenizer.try_set_collateral(
+            &token_eurc.address,
+            &Collateral {
+                decimals: 7,
+                rate: ConversionRate::Fixed(0),
+            }
+        ),
+        Err(Ok(ExcellarError::InvalidCollateral))
+    );
+
+    // 1 EURC = 1.1 USDC
+    tokenizer.set_collateral(
+        &token_eurc.address,
+        &Collateral {
+            decimals: 7,
+            rate: ConversionRate::Fixed(11 * USDC_UNIT / 10),
+        },
+    );
+    let deposit = tokenizer.deposit(&user1, &token_eurc.address, &1_000);
+    assert_eq!(deposit.value, 1_100);
+    assert_eq!(deposit.xusg, 1_100);
+    assert_eq!(tokenizer.cash_reserves(), 1_100);
+    assert_eq!(tokenizer.asset_reserves(&token_eurc.address), 1_000);
+    assert_eq!(tokenizer.asset_reserves(&token_usdc.address), 0);
+
+    let withdrawal = tokenizer.withdraw(&user1, &token_eurc.address, &550);
+    assert_eq!(withdrawal.value, 550);
+    assert_eq!(withdrawal.net, 500);
+    assert_eq!(token_eurc.balance(&user1), 500);
+    assert_eq!(tokenizer.cash_reserves(), 550);
+    assert_eq!(tokenizer.asset_reserves(&token_eurc.address), 500);
+
+    // There is no USDC to pay out yet.
+    assert_eq!(
+        tokenizer.try_withdraw(&user1, &token_usdc.address, &10),
+        Err(Ok(ExcellarError::InsufficientLiquidity))
+    );
+
+    // 1 USDT = 0.98 USDC, as reported with the oracle's 14 decimals.
+    oracle.set_price(&(98 * ORACLE_UNIT / 100), &0);
+    tokenizer.set_collateral(
+        &token_usdt.address,
+        &Collateral {
+            decimals: 7,
+            rate: ConversionRate::Oracle(OracleConfig {
+                oracle: oracle.address.clone(),
+                asset: Asset::Stellar(token_usdt.address.clone()),
+            }),
+        },
+    );
+    assert_eq!(
+        tokenizer.collateral_assets(),
+        vec![
+            &e,
+            token_usdc.address.clone(),
+            token_eurc.address.clone(),
+            token_usdt.address.clone()
+        ]
+    );
+    assert_eq!(
+        tokenizer.deposit(&user1, &token_usdt.address, &1_000).value,
+        980
+    );
+    assert_eq!(tokenizer.cash_reserves(), 1_530);
+
+    // Redemptions are paid in the asset they were requested in.
+    let id = tokenizer.request_redeem(&user1, &token_usdt.address, &490);
+    assert_eq!(tokenizer.redemption(&id).asset, token_usdt.address);
+    assert_eq!(tokenizer.fulfil_redemptions(&1), 1);
+    assert_eq!(token_usdt.balance(&user1), 500);
+    assert_eq!(tokenizer.asset_reserves(&token_usdt.address), 500);
+
+    // The EURC on hand has to be moved out before it can be delisted.
+    assert_eq!(
+        tokenizer.try_remove_collateral(&token_eurc.address),
+        Err(Ok(ExcellarError::CollateralFunded))
+    );
+    tokenizer.withdraw_admin(&admin1, &admin1, &token_eurc.address, &500);
+    tokenizer.remove_collateral(&token_eurc.address);
+    assert_eq!(tokenizer.collateral(&token_eurc.address), None);
+    assert_eq!(
+        tokenizer.try_request_redeem(&user1, &token_eurc.address, &10),
+        Err(Ok(ExcellarError::UnsupportedCollateral))
+    );
+    assert_eq!(
+        tokenizer.try_remove_collateral(&token_usdc.address),
+        Err(Ok(ExcellarError::InvalidCollateral))
+    );
 }
@@ -0,0 +1,152 @@
+use crate::admin::PendingAdmin;
+use crate::collateral::Collateral;
+use crate::custodian::NavSigners;
+use crate::error::{AccessError, GovernanceError};
+use crate::fees::TransactionFees;
+use crate::limits::{Limits, LiquidityBuffer};
+use crate::oracle::OracleConfig;
+use crate::redemption::RedemptionPriceMode;
+use crate::roles::Role;
+use crate::storage::{get_record, remove_record, set_record, DataKey, RecordKey};
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, BytesN, Env, Vec};
+
+/// Longest delay the timelock accepts, so a mistaken setting can't lock the
+/// queued operations away for good.
+pub const MAX_TIMELOCK_DELAY: u64 = 30 * 86_400;
+
+/// A `withdraw_admin` held in the timelock.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AdminWithdrawal {
+    pub treasurer: Address,
+    pub to: Address,
+    pub asset: Address,
+    pub amount: i128,
+}
+
+/// A `collect_fees` held in the timelock.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeCollection {
+    pub treasurer: Address,
+    pub to: Address,
+}
+
+/// A `grant_role` or `revoke_role` held in the timelock.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RoleChange {
+    pub role: Role,
+    pub account: Address,
+}
+
+/// A `set_collateral` held in the timelock.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CollateralChange {
+    pub asset: Address,
+    pub collateral: Collateral,
+}
+
+/// The sensitive calls that go through the timelock once a delay is set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TimelockCall {
+    WithdrawAdmin(AdminWithdrawal),
+    SetManagementFee(u32),
+    SetTransactionFees(TransactionFees),
+    ProposeAdmin(PendingAdmin),
+    SetTimelockDelay(u64),
+    Upgrade(BytesN<32>),
+    CollectFees(FeeCollection),
+    GrantRole(RoleChange),
+    /// Only needed for `Guardian`; other roles are revoked directly.
+    RevokeRole(RoleChange),
+    SetCollateral(CollateralChange),
+    RemoveCollateral(Address),
+    SetCollateralHeartbeat(u64),
+    SetOracle(OracleConfig),
+    RemoveOracle,
+    SetOracleMaxDeviation(u32),
+    SetNavHeartbeat(u64),
+    SetNavSigners(NavSigners),
+    SetLimits(Limits),
+    SetLiquidityBuffer(LiquidityBuffer),
+    SetRedemptionPriceMode(RedemptionPriceMode),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ScheduledOperation {
+    pub call: TimelockCall,
+    pub scheduled_at: u64,
+    /// Ledger timestamp from which the operation can be executed.
+    pub ready_at: u64,
+}
+
+/// Seconds between scheduling a sensitive call and executing it. Zero, the
+/// default, lets those calls be made directly.
+pub fn get_timelock_delay(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&DataKey::TimelockDelay)
+        .unwrap_or(0)
+}
+
+pub fn set_timelock_delay(e: &Env, seconds: u64) -> Result<(), GovernanceError> {
+    if seconds > MAX_TIMELOCK_DELAY {
+        return Err(GovernanceError::InvalidTimelockDelay);
+    }
+
+    e.storage()
+        .instance()
+        .set(&DataKey::TimelockDelay, &seconds);
+    Ok(())
+}
+
+/// Fails for direct sensitive calls while the timelock is enabled.
+pub fn require_no_timelock(e: &Env) -> Result<(), AccessError> {
+    if get_timelock_delay(e) > 0 {
+        return Err(AccessError::TimelockRequired);
+    }
+    Ok(())
+}
+
+pub fn operation_hash(e: &Env, call: &TimelockCall, salt: &BytesN<32>) -> BytesN<32> {
+    e.crypto().sha256(&(call.clone(), salt.clone()).to_xdr(e))
+}
+
+/// Hashes of the scheduled operations, oldest first.
+pub fn get_timelock_queue(e: &Env) -> Vec<BytesN<32>> {
+    e.storage()
+        .instance()
+        .get(&DataKey::TimelockQueue)
+        .unwrap_or(Vec::new(e))
+}
+
+fn set_timelock_queue(e: &Env, queue: &Vec<BytesN<32>>) {
+    e.storage().instance().set(&DataKey::TimelockQueue, queue)
+}
+
+pub fn get_scheduled_operation(e: &Env, hash: &BytesN<32>) -> Option<ScheduledOperation> {
+    get_record(e, &RecordKey::TimelockOperation(hash.clone()))
+}
+
+pub fn push_scheduled_operation(e: &Env, hash: &BytesN<32>, operation: &ScheduledOperation) {
+    set_record(e, &RecordKey::TimelockOperation(hash.clone()), operation);
+    let mut queue = get_timelock_queue(e);
+    queue.push_back(hash.clone());
+    set_timelock_queue(e, &queue);
+}
+
+/// Takes an operation out of the queue, returning it if it was scheduled.
+pub fn take_scheduled_operation(e: &Env, hash: &BytesN<32>) -> Option<ScheduledOperation> {
+    let operation = get_scheduled_operation(e, hash)?;
+    remove_record(e, &RecordKey::TimelockOperation(hash.clone()));
+    let mut queue = get_timelock_queue(e);
+    if let Some(index) = queue.first_index_of(hash) {
+        queue.remove(index);
+        set_timelock_queue(e, &queue);
+    }
+    Some(operation)
+}
@@ -1,13 +1,28 @@
 #![allow(unused)]
-use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env};
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, String};
 
 soroban_sdk::contractimport!(
     file = "../token/target/wasm32-unknown-unknown/release/excellar_token_contract.wasm"
 );
 
-pub fn create_contract(e: &Env, token_wasm_hash: BytesN<32>, token_usdc: &Address) -> Address {
+/// Metadata of the share token a tokenizer mints.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ShareToken {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+pub fn create_contract(
+    e: &Env,
+    token_wasm_hash: BytesN<32>,
+    token_usdc: &Address,
+    share: &ShareToken,
+) -> Address {
     let mut salt = Bytes::new(e);
     salt.append(&token_usdc.to_xdr(e));
+    salt.append(&share.symbol.clone().to_xdr(e));
     let salt = e.crypto().sha256(&salt);
     e.deployer()
         .with_current_contract(salt)
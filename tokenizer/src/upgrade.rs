@@ -0,0 +1,116 @@
+use crate::allowlist::set_allowlist_enabled;
+use crate::collateral::{base_collateral, set_asset_reserves, set_collateral};
+use crate::error::{AccessError, GovernanceError};
+use crate::fees::{set_fees_accrued_at, set_management_fee_bps};
+use crate::oracle::set_nav_updated_at;
+use crate::redemption::{set_redemption_price_mode, RedemptionPriceMode};
+use crate::roles::{grant_role, Role};
+use crate::storage::{
+    bump_instance, get_balance_usdc, get_token_usdc, set_price_decimals, DataKey,
+};
+use soroban_sdk::{Address, Env, IntoVal, Val};
+
+/// Version of the storage layout written by this build of the contract.
+/// Version 1 is the baseline layout, which kept its seven `DataKey` entries
+/// in persistent storage and did not store a version.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Price precision given to contracts migrated from version 1, matching the
+/// 7 decimals of Stellar assets.
+pub const V1_MIGRATED_PRICE_DECIMALS: u32 = 7;
+
+/// Every `DataKey` that version 1 wrote.
+const V1_DATA_KEYS: [DataKey; 7] = [
+    DataKey::TokenUSDC,
+    DataKey::TokenXUSG,
+    DataKey::TotalXUSG,
+    DataKey::MarketETFPrice,
+    DataKey::ReservesCash,
+    DataKey::Admin,
+    DataKey::Fees,
+];
+
+/// Contracts deployed before the version was stored are on version 1.
+pub fn get_schema_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::SchemaVersion)
+        .unwrap_or(1)
+}
+
+/// The admin under any layout, so that contracts that have not been
+/// migrated yet can still authorize `migrate`.
+pub fn get_stored_admin(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .or_else(|| e.storage().persistent().get(&DataKey::Admin))
+}
+
+pub fn require_stored_admin(e: &Env) -> Result<(), AccessError> {
+    get_stored_admin(e)
+        .ok_or(AccessError::NotInitialized)?
+        .require_auth();
+    Ok(())
+}
+
+pub fn set_schema_version(e: &Env, version: u32) {
+    e.storage()
+        .instance()
+        .set(&DataKey::SchemaVersion, &version)
+}
+
+/// Brings the stored layout up to `SCHEMA_VERSION` and returns the version
+/// it ended on.
+pub fn migrate(e: &Env) -> Result<u32, GovernanceError> {
+    match get_schema_version(e) {
+        1 => migrate_v1(e),
+        SCHEMA_VERSION => {}
+        _ => return Err(GovernanceError::UnknownSchemaVersion),
+    }
+    set_schema_version(e, SCHEMA_VERSION);
+
+    bump_instance(e);
+    Ok(SCHEMA_VERSION)
+}
+
+/// Moves the version 1 entries to instance storage and fills in everything
+/// added since with values that keep the contract behaving as before.
+fn migrate_v1(e: &Env) {
+    for key in V1_DATA_KEYS {
+        move_to_instance(e, &key);
+    }
+
+    // Version 1 priced XUSG in whole USDC, truncating the fraction. Nothing
+    // stored in version 1 holds a price, so the precision can be raised.
+    set_price_decimals(e, V1_MIGRATED_PRICE_DECIMALS);
+
+    // The stored fee amount stays owed; accrual starts from the migration.
+    set_management_fee_bps(e, 0);
+    set_fees_accrued_at(e, e.ledger().timestamp());
+
+    set_nav_updated_at(e, e.ledger().timestamp());
+    set_redemption_price_mode(e, RedemptionPriceMode::AtFulfilment);
+
+    let admin = get_stored_admin(e).unwrap();
+    grant_role(e, Role::Reporter, admin.clone());
+    grant_role(e, Role::Treasurer, admin.clone());
+    grant_role(e, Role::Pauser, admin.clone());
+    grant_role(e, Role::Compliance, admin);
+
+    // Existing holders were never onboarded, so the allowlist stays off
+    // until compliance has added them.
+    set_allowlist_enabled(e, false);
+
+    // Version 1 only took USDC, so everything on hand is base collateral.
+    let token_usdc = get_token_usdc(e);
+    set_collateral(e, token_usdc.clone(), &base_collateral(e));
+    set_asset_reserves(e, &token_usdc, get_balance_usdc(e));
+}
+
+fn move_to_instance<K: IntoVal<Env, Val>>(e: &Env, key: &K) {
+    if let Some(value) = e.storage().persistent().get::<K, Val>(key) {
+        e.storage().instance().set(key, &value);
+        e.storage().persistent().remove(key);
+    }
+}
@@ -1,10 +1,46 @@
-pub fn require_positive(amount: i128) {
+use crate::error::ExcellarError;
+
+pub fn require_positive(amount: i128) -> Result<(), ExcellarError> {
     if amount < 0 {
-        panic!("amount must be positive");
+        return Err(ExcellarError::NegativeAmount);
     }
+    Ok(())
 }
-pub fn require_strictly_positive(amount: i128) {
+pub fn require_strictly_positive(amount: i128) -> Result<(), ExcellarError> {
     if amount <= 0 {
-        panic!("amount must be strictly positive");
+        return Err(ExcellarError::AmountMustBePositive);
     }
+    Ok(())
+}
+
+/// `a + b`, failing with `ArithmeticOverflow` instead of trapping.
+pub fn checked_add(a: i128, b: i128) -> Result<i128, ExcellarError> {
+    a.checked_add(b).ok_or(ExcellarError::ArithmeticOverflow)
+}
+
+/// `a - b`, failing with `ArithmeticOverflow` instead of trapping.
+pub fn checked_sub(a: i128, b: i128) -> Result<i128, ExcellarError> {
+    a.checked_sub(b).ok_or(ExcellarError::ArithmeticOverflow)
+}
+
+/// `10^decimals`, failing with `ArithmeticOverflow` for precisions an i128
+/// can't hold.
+pub fn pow10(decimals: u32) -> Result<i128, ExcellarError> {
+    10_i128
+        .checked_pow(decimals)
+        .ok_or(ExcellarError::ArithmeticOverflow)
+}
+
+pub fn mul_div(a: i128, b: i128, denominator: i128) -> Result<i128, ExcellarError> {
+    a.checked_mul(b)
+        .and_then(|product| product.checked_div(denominator))
+        .ok_or(ExcellarError::ArithmeticOverflow)
+}
+
+/// `mul_div` rounded up, for non-negative amounts owed to the fund.
+pub fn mul_div_ceil(a: i128, b: i128, denominator: i128) -> Result<i128, ExcellarError> {
+    a.checked_mul(b)
+        .and_then(|product| product.checked_add(denominator - 1))
+        .and_then(|product| product.checked_div(denominator))
+        .ok_or(ExcellarError::ArithmeticOverflow)
 }